@@ -0,0 +1,40 @@
+//! Convert the vocabulary workbook to JSON loaded by `WordsGame::load_words_json`:
+//! `cargo run --bin xlsx_to_json -- woerterbuch.xlsx words.json`
+
+use std::process::ExitCode;
+
+use calamine::Reader;
+use dw_web::words::Database;
+
+fn convert(input: &str, output: &str) -> Result<usize, String> {
+    let mut workbook = calamine::open_workbook_auto(input).map_err(|e| e.to_string())?;
+    let range = workbook
+        .worksheet_range("Words")
+        .ok_or("No sheet called Words")?
+        .map_err(|e| e.to_string())?;
+    let mut db = Database::new();
+    db.read_sheet(&range);
+    for warning in db.warnings.iter() {
+        eprintln!("Warning: {}", warning);
+    }
+    std::fs::write(output, db.to_json()).map_err(|e| e.to_string())?;
+    Ok(db.words.len())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    if args.len() != 3 {
+        eprintln!("Usage: {} <input.xlsx> <output.json>", args[0]);
+        return ExitCode::FAILURE;
+    }
+    match convert(&args[1], &args[2]) {
+        Ok(count) => {
+            println!("Converted {} words", count);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
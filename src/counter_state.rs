@@ -14,12 +14,12 @@ impl CounterState {
     }
 
     pub fn increment_counter(&mut self) -> i32 {
-        self.counter = self.counter + 1;
+        self.counter += 1;
         self.counter
     }
 
     pub fn decrement_counter(&mut self) -> i32 {
-        self.counter = self.counter - 1;
+        self.counter -= 1;
         self.counter
     }
 
@@ -1,41 +1,282 @@
+use crate::messages::{Message, MessageId, UiLanguage};
 use crate::words::*;
 use rand::distributions::WeightedIndex;
 use rand::prelude::*;
+use rand::rngs::StdRng;
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Display;
 use std::{cmp::Ordering, vec};
 use strum::IntoEnumIterator;
-use strum_macros::EnumIter;
+use strum_macros::{EnumIter, EnumString};
+use tsify::Tsify;
 
 const ANSWER_OPTIONS: usize = 4;
+const MATCH_PAIRS_MIN: usize = 4;
+const MATCH_PAIRS_MAX: usize = 6;
+/// Shorter words are easy to spell
+const ANAGRAM_MIN_LENGTH: usize = 6;
+/// Reshuffles of the letters that happened to keep the word as it is
+const ANAGRAM_SHUFFLE_TRIES: usize = 6;
+const DEFAULT_COOLDOWN_EXERCISES: usize = 3;
+/// Weights of the features making a distractor confusable with the correct answer
+const DISTRACTOR_BASE_WEIGHT: f64 = 0.1;
+const DISTRACTOR_SPELLING_WEIGHT: f64 = 2.0;
+const DISTRACTOR_LENGTH_WEIGHT: f64 = 0.5;
+const DISTRACTOR_ARTICLE_WEIGHT: f64 = 0.5;
+const DISTRACTOR_CONFUSION_WEIGHT: f64 = 3.0;
+/// Correct answers in a row for a new word to join the general rotation
+const LEARNING_THRESHOLD: usize = 3;
+/// New words are introduced while there are less words in learning
+const MAX_LEARNING_WORDS: usize = 5;
+/// Words in learning are chosen more often than the rest
+const LEARNING_WEIGHT_FACTOR: f32 = 4.0;
+/// Score penalty of the hints of one answer, less than the 3 points
+/// between a correct and a wrong answer
+const MAX_HINT_PENALTY: usize = 2;
+pub const LEITNER_BOXES: usize = 5;
+/// Relative review frequency of the words in each Leitner box
+const LEITNER_BOX_FREQUENCY: [f32; LEITNER_BOXES] = [16.0, 8.0, 4.0, 2.0, 1.0];
 
 #[allow(unused)]
-#[derive(EnumIter, Debug)]
+#[derive(EnumIter, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Tsify)]
 pub enum ExerciseType {
     SelectDe,
-    TranslateRuDe,
-    SelectRu,
+    /// Saved results and configs may use the names from the time of Russian only translations
+    #[serde(alias = "TranslateRuDe")]
+    TranslateNativeDe,
+    #[serde(alias = "SelectRu")]
+    SelectNative,
     GuessNounArticle,
     VerbFormRandom,
+    Confusables,
+    MatchPairs,
+    Anagram,
+    Cloze,
 }
+
+#[derive(Debug, EnumIter, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+pub enum VerbFormExercise {
+    PresentThird,
+    Praeteritum,
+    Perfect,
+}
+
+/// Exercise type together with the verb form for verb form exercises,
+/// results are kept separately for each of them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Tsify)]
+pub struct ExerciseKey {
+    pub ex_type: ExerciseType,
+    pub verb_form: Option<VerbFormExercise>,
+}
+
+impl ExerciseKey {
+    pub fn new(ex_type: ExerciseType) -> Self {
+        Self {
+            ex_type,
+            verb_form: None,
+        }
+    }
+
+    pub fn verb_form(form: VerbFormExercise) -> Self {
+        Self {
+            ex_type: ExerciseType::VerbFormRandom,
+            verb_form: Some(form),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExerciseTypeResults {
+    #[serde(flatten)]
+    key: ExerciseKey,
+    correct: usize,
+    wrong: usize,
+    /// Score penalty of the hints used for the correct answers
+    #[serde(default)]
+    hints: usize,
+}
+
+impl ExerciseTypeResults {
+    pub fn score(&self) -> i32 {
+        self.correct as i32 - (self.wrong * 2) as i32 - self.hints as i32
+    }
+
+    pub fn key(&self) -> &ExerciseKey {
+        &self.key
+    }
+
+    pub fn correct(&self) -> usize {
+        self.correct
+    }
+
+    pub fn wrong(&self) -> usize {
+        self.wrong
+    }
+}
+
+/// Strategy to choose the next word to learn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, Tsify)]
+pub enum Scheduler {
+    /// Words with lower scores in the exercise are chosen more often
+    #[default]
+    Weights,
+    /// Words move between the Leitner boxes, lower boxes are reviewed more often
+    Leitner,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum WordStage {
+    /// Not introduced to the learner yet
+    #[default]
+    New,
+    /// Introduced, but not answered correctly `LEARNING_THRESHOLD` times in a row
+    Learning,
+    Known,
+}
+
+/// Status of a word set by the learner
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, EnumString)]
+pub enum WordStatus {
+    #[default]
+    Active,
+    /// Known to the learner, not selected for exercises but still used as an answer option
+    Mastered,
+    /// Skipped everywhere, e.g. the row of the sheet is wrong
+    Suspended,
+}
+
+/// Limit of the new words introduced to the learner
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NewWordsLimit {
+    PerDay(usize),
+    PerSession(usize),
+}
+
+fn default_leitner_box() -> usize {
+    1
+}
+
 #[derive(Debug, Serialize, Deserialize, Default)]
 pub struct ExerciseResults {
+    /// Id of the word, see `Word::get_id`
     word: String,
     correct: usize,
     wrong: usize,
+    #[serde(default)]
+    exercises: Vec<ExerciseTypeResults>,
+    /// Leitner box from 1 to `LEITNER_BOXES`
+    #[serde(default = "default_leitner_box")]
+    leitner_box: usize,
+    #[serde(default)]
+    stage: WordStage,
+    /// Correct answers in a row
+    #[serde(default)]
+    streak: usize,
+    /// Day when the word was introduced as YYYY-MM-DD
+    #[serde(default)]
+    introduced: Option<String>,
+    #[serde(default)]
+    status: WordStatus,
+    /// Score penalty of the hints used for the correct answers,
+    /// each hint costs a point up to `MAX_HINT_PENALTY` per answer
+    #[serde(default)]
+    hints: usize,
 }
 
 impl ExerciseResults {
-    pub fn add(&mut self, correct: bool) {
-        if correct {
+    /// Correct answer with hints gets reduced credit: the word moves one Leitner box back
+    /// instead of forward and the streak starts over, but not as far as after a wrong answer
+    pub fn add(&mut self, key: &ExerciseKey, correct: bool, hints: usize) {
+        let idx = match self.exercises.iter().position(|ex| &ex.key == key) {
+            Some(i) => i,
+            None => {
+                self.exercises.push(ExerciseTypeResults {
+                    key: *key,
+                    correct: 0,
+                    wrong: 0,
+                    hints: 0,
+                });
+                self.exercises.len() - 1
+            }
+        };
+        let ex = &mut self.exercises[idx];
+        if correct && hints > 0 {
+            let penalty = hints.min(MAX_HINT_PENALTY);
+            self.correct += 1;
+            ex.correct += 1;
+            self.hints += penalty;
+            ex.hints += penalty;
+            self.leitner_box = self.leitner_box.saturating_sub(1).max(1);
+            self.streak = 0;
+        } else if correct {
             self.correct += 1;
+            ex.correct += 1;
+            self.leitner_box = (self.leitner_box + 1).min(LEITNER_BOXES);
+            self.streak += 1;
         } else {
             self.wrong += 1;
+            ex.wrong += 1;
+            self.leitner_box = 1;
+            self.streak = 0;
         }
+        self.stage = match self.stage {
+            WordStage::Known => WordStage::Known,
+            _ if self.streak >= LEARNING_THRESHOLD => WordStage::Known,
+            _ => WordStage::Learning,
+        };
+    }
+
+    pub fn stage(&self) -> WordStage {
+        self.stage
+    }
+
+    /// Stage of a word practiced before the stages were saved
+    fn legacy_stage(&self) -> WordStage {
+        // Every correct answer in a row moves the word to the next box starting from the first one
+        if self.correct + self.wrong == 0 {
+            WordStage::New
+        } else if self.streak >= LEARNING_THRESHOLD || self.leitner_box > LEARNING_THRESHOLD {
+            WordStage::Known
+        } else {
+            WordStage::Learning
+        }
+    }
+
+    pub fn status(&self) -> WordStatus {
+        self.status
+    }
+
+    pub fn leitner_box(&self) -> usize {
+        self.leitner_box
     }
 
     pub fn score(&self) -> i32 {
-        self.correct as i32 - (self.wrong * 2) as i32
+        self.correct as i32 - (self.wrong * 2) as i32 - self.hints as i32
+    }
+
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    pub fn correct(&self) -> usize {
+        self.correct
+    }
+
+    pub fn wrong(&self) -> usize {
+        self.wrong
+    }
+
+    pub fn exercises(&self) -> &[ExerciseTypeResults] {
+        &self.exercises
+    }
+
+    /// Score of the word in the given exercise only, zero if it was never asked there
+    pub fn exercise_score(&self, key: &ExerciseKey) -> i32 {
+        self.exercises
+            .iter()
+            .find(|ex| &ex.key == key)
+            .map_or(0, |ex| ex.score())
     }
 
     pub fn new(s: &str) -> Self {
@@ -43,6 +284,13 @@ impl ExerciseResults {
             correct: 0,
             wrong: 0,
             word: s.to_owned(),
+            exercises: vec![],
+            leitner_box: 1,
+            stage: WordStage::New,
+            streak: 0,
+            introduced: None,
+            status: WordStatus::Active,
+            hints: 0,
         }
     }
 }
@@ -67,94 +315,571 @@ impl PartialEq for ExerciseResults {
 
 impl Eq for ExerciseResults {}
 
-#[derive(Debug, EnumIter)]
-enum VerbFormExercise {
-    PresentThird,
-    Praeteritum,
-    Perfect,
+#[derive(Debug, Serialize, Deserialize, Tsify, Clone)]
+pub struct DayResults {
+    /// Date as YYYY-MM-DD
+    day: String,
+    correct: usize,
+    wrong: usize,
+}
+
+/// Wrong answer option chosen by the learner instead of the word
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Confusion {
+    word: String,
+    confused_with: String,
+    count: usize,
+}
+
+impl Confusion {
+    pub fn word(&self) -> &str {
+        &self.word
+    }
+
+    pub fn confused_with(&self) -> &str {
+        &self.confused_with
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+/// Results stored between the game sessions, see `GameResults::to_json`
+#[derive(Debug, Deserialize)]
+struct SavedResults {
+    results: Vec<SavedWordResults>,
+    #[serde(default)]
+    history: Vec<DayResults>,
+    #[serde(default)]
+    confusions: Vec<Confusion>,
+}
+
+/// Results saved before the stages were introduced have no stage
+#[derive(Debug, Deserialize)]
+struct SavedWordResults {
+    #[serde(flatten)]
+    results: ExerciseResults,
+    stage: Option<WordStage>,
+}
+
+impl SavedWordResults {
+    fn into_results(self) -> ExerciseResults {
+        let mut res = self.results;
+        res.stage = self.stage.unwrap_or_else(|| res.legacy_stage());
+        res
+    }
+}
+
+/// How long a word is not repeated after it was selected for an exercise
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Cooldown {
+    Exercises(usize),
+    Seconds(f64),
 }
 
 pub struct GameResults {
     results: Vec<ExerciseResults>,
-    results_filename: String,
-    weights: Vec<f32>,
-    rand_dist: Option<WeightedIndex<f32>>,
-    training: Vec<String>,
+    history: Vec<DayResults>,
+    confusions: Vec<Confusion>,
+    groups: Option<HashSet<usize>>,
+    scheduler: Scheduler,
+    cooldown: Cooldown,
+    /// Recently selected words with the time of selection in seconds
+    recent: VecDeque<(String, f64)>,
+    time: f64,
+    /// Current date as YYYY-MM-DD
+    day: String,
+    new_words_limit: Option<NewWordsLimit>,
+    session_introduced: usize,
 }
 
 impl GameResults {
     pub fn new() -> Self {
         GameResults {
             results: vec![],
-            results_filename: String::new(),
-            weights: vec![],
-            rand_dist: None,
-            training: vec![],
+            history: vec![],
+            confusions: vec![],
+            groups: None,
+            scheduler: Scheduler::Weights,
+            cooldown: Cooldown::Exercises(DEFAULT_COOLDOWN_EXERCISES),
+            recent: VecDeque::new(),
+            time: 0.0,
+            day: String::new(),
+            new_words_limit: None,
+            session_introduced: 0,
         }
     }
 
-    pub fn get_training_words(&self) -> &Vec<String> {
-        &self.training
+    pub fn set_scheduler(&mut self, scheduler: Scheduler) {
+        self.scheduler = scheduler;
     }
 
-    pub fn update_with_db(&mut self, db: &Database) {
-        for word in db.words.keys() {
-            let new_entry = ExerciseResults::new(word);
-            if !self.results.contains(&new_entry) {
-                self.results.push(new_entry);
+    /// Number of the words of the database in each Leitner box
+    pub fn get_leitner_boxes(&self, db: &Database) -> [usize; LEITNER_BOXES] {
+        let mut boxes = [0; LEITNER_BOXES];
+        for res in self.results.iter() {
+            if db.words.contains_key(&res.word) {
+                boxes[res.leitner_box().clamp(1, LEITNER_BOXES) - 1] += 1;
             }
         }
-        self.results.sort_unstable()
+        boxes
+    }
+
+    pub fn set_cooldown(&mut self, cooldown: Cooldown) {
+        self.cooldown = cooldown;
     }
 
-    pub fn get_top_words(&self, n: usize) -> Vec<String> {
+    /// Current time in seconds and date as YYYY-MM-DD
+    pub fn set_clock(&mut self, time: f64, day: &str) {
+        self.time = time;
+        self.day = day.to_owned();
+    }
+
+    /// Introduce unseen words gradually, `None` to learn all words at once
+    pub fn set_new_words_limit(&mut self, limit: Option<NewWordsLimit>) {
+        self.new_words_limit = limit;
+    }
+
+    pub fn start_session(&mut self) {
+        self.session_introduced = 0;
+    }
+
+    fn can_introduce_word(&self) -> bool {
+        match self.new_words_limit {
+            None => false,
+            Some(NewWordsLimit::PerSession(limit)) => self.session_introduced < limit,
+            Some(NewWordsLimit::PerDay(limit)) => {
+                let today = Some(&self.day);
+                let introduced = self
+                    .results
+                    .iter()
+                    .filter(|r| r.introduced.as_ref() == today)
+                    .count();
+                introduced < limit
+            }
+        }
+    }
+
+    /// First word not introduced yet in the learning order of the database
+    fn next_new_word<T>(
+        &self,
+        db: &Database,
+        cmp: &impl Fn(&Word, &T) -> bool,
+        prop: &T,
+    ) -> Option<usize> {
+        let new_words: HashMap<&str, usize> = self
+            .results
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| r.stage == WordStage::New && r.status == WordStatus::Active)
+            .map(|(i, r)| (r.word.as_str(), i))
+            .collect();
+        db.words_in_learning_order()
+            .into_iter()
+            .filter(|w| self.is_word_in_groups(w) && cmp(w, prop))
+            .find_map(|w| new_words.get(w.get_id()).copied())
+    }
+
+    fn introduce_word(&mut self, idx: usize) {
+        let res = &mut self.results[idx];
+        res.stage = WordStage::Learning;
+        res.introduced = Some(self.day.clone());
+        self.session_introduced += 1;
+    }
+
+    fn is_recent(&self, word: &str) -> bool {
+        match self.cooldown {
+            Cooldown::Exercises(n) => self.recent.iter().rev().take(n).any(|(w, _)| w == word),
+            Cooldown::Seconds(secs) => self
+                .recent
+                .iter()
+                .any(|(w, t)| w == word && self.time - t < secs),
+        }
+    }
+
+    fn mark_recent(&mut self, word: &str) {
+        self.recent.push_back((word.to_owned(), self.time));
+        match self.cooldown {
+            Cooldown::Exercises(n) => {
+                while self.recent.len() > n {
+                    self.recent.pop_front();
+                }
+            }
+            Cooldown::Seconds(secs) => {
+                while let Some((_, t)) = self.recent.front() {
+                    if self.time - t < secs {
+                        break;
+                    }
+                    self.recent.pop_front();
+                }
+            }
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "results": self.results,
+            "history": self.history,
+            "confusions": self.confusions,
+        })
+        .to_string()
+    }
+
+    /// Replace the results with the ones saved by `to_json`,
+    /// words missing in the saved results are kept
+    pub fn load_json(&mut self, json: &str) -> Result<(), String> {
+        let saved: SavedResults =
+            serde_json::from_str(json).map_err(|e| format!("Invalid saved results: {}", e))?;
+        for entry in saved
+            .results
+            .into_iter()
+            .map(SavedWordResults::into_results)
+        {
+            match self.results.iter().position(|r| r == &entry) {
+                Some(i) => self.results[i] = entry,
+                None => self.results.push(entry),
+            }
+        }
+        self.history = saved.history;
+        self.confusions = saved.confusions;
+        self.results.sort();
+        Ok(())
+    }
+
+    pub fn results(&self) -> &[ExerciseResults] {
+        &self.results
+    }
+
+    pub fn history(&self) -> &[DayResults] {
+        &self.history
+    }
+
+    pub fn confusions(&self) -> &[Confusion] {
+        &self.confusions
+    }
+
+    /// Restrict the words to learn to the given groups, `None` to learn all groups
+    pub fn set_groups(&mut self, groups: Option<HashSet<usize>>) {
+        self.groups = groups;
+    }
+
+    /// Returns false if the word is not in the results
+    pub fn set_word_status(&mut self, word: &str, status: WordStatus) -> bool {
+        match self.results.iter_mut().find(|r| r.word == word) {
+            None => false,
+            Some(res) => {
+                res.status = status;
+                true
+            }
+        }
+    }
+
+    pub fn get_words_with_status(&self, status: WordStatus) -> Vec<&str> {
         self.results
             .iter()
-            .take(n)
-            .map(|r| r.word.to_owned())
+            .filter(|r| r.status == status)
+            .map(|r| r.word.as_str())
             .collect()
     }
 
-    fn select_word_to_learn(&mut self) -> &mut ExerciseResults {
-        let mut rng = rand::thread_rng();
-        let dist = self.rand_dist.as_ref().unwrap();
-        &mut self.results[dist.sample(&mut rng)]
+    /// Status of every word in the results, to check many words without scanning the results
+    fn word_statuses(&self) -> HashMap<&str, WordStatus> {
+        self.results
+            .iter()
+            .map(|r| (r.word.as_str(), r.status))
+            .collect()
     }
 
-    fn select_word_by_cmp<T>(
+    fn is_word_in_groups(&self, word: &Word) -> bool {
+        match &self.groups {
+            None => true,
+            Some(groups) => groups.contains(&word.get_group_id()),
+        }
+    }
+
+    /// Results saved before the word ids were introduced are keyed by the word itself,
+    /// the ones of the words with several meanings cannot be migrated and are reported
+    fn migrate_to_word_ids(&mut self, db: &Database) -> Vec<String> {
+        let mut ids: HashMap<&str, Vec<&str>> = HashMap::new();
+        for word in db.words_in_order() {
+            ids.entry(word.get_word()).or_default().push(word.get_id());
+        }
+        let id_of = |key: &str| match ids.get(key).map(|v| v.as_slice()) {
+            Some([id]) if !db.words.contains_key(key) => Some(id.to_string()),
+            _ => None,
+        };
+        let known: HashSet<String> = self.results.iter().map(|r| r.word.clone()).collect();
+        let mut warnings = vec![];
+        for res in self.results.iter_mut() {
+            if let Some(id) = id_of(&res.word).filter(|id| !known.contains(id)) {
+                res.word = id;
+            } else if let Some(homographs) = ids.get(res.word.as_str()).filter(|v| v.len() > 1) {
+                warnings.push(format!(
+                    "Saved results of the word {} are not used, it is one of {}",
+                    res.word,
+                    homographs.join(", ")
+                ));
+            }
+        }
+        for c in self.confusions.iter_mut() {
+            if let Some(id) = id_of(&c.word) {
+                c.word = id;
+            }
+            if let Some(id) = id_of(&c.confused_with) {
+                c.confused_with = id;
+            }
+        }
+        warnings
+    }
+
+    /// Returns warnings about the saved results that do not match the words
+    pub fn update_with_db(&mut self, db: &Database) -> Vec<String> {
+        let warnings = self.migrate_to_word_ids(db);
+        for word in db.words_in_order() {
+            let new_entry = ExerciseResults::new(word.get_id());
+            if !self.results.contains(&new_entry) {
+                self.results.push(new_entry);
+            }
+        }
+        self.results.sort();
+        warnings
+    }
+
+    /// Words with the lowest score among the ones already practiced
+    pub fn get_top_words(&self, n: usize) -> Vec<&ExerciseResults> {
+        let mut practiced: Vec<_> = self
+            .results
+            .iter()
+            .filter(|r| r.correct + r.wrong > 0)
+            .collect();
+        practiced.sort();
+        practiced.into_iter().take(n).collect()
+    }
+
+    pub fn add_result(&mut self, word: &str, key: &ExerciseKey, correct: bool, hints: usize) {
+        if let Some(res) = self.results.iter_mut().find(|r| r.word == word) {
+            res.add(key, correct, hints);
+        }
+        if self.history.last().map(|d| &d.day) != Some(&self.day) {
+            self.history.push(DayResults {
+                day: self.day.clone(),
+                correct: 0,
+                wrong: 0,
+            });
+        }
+        let today = self.history.last_mut().unwrap();
+        if correct {
+            today.correct += 1;
+        } else {
+            today.wrong += 1;
+        }
+    }
+
+    /// Every word of the matching exercise graded by `Exercise::check_pairs` gets its own result,
+    /// a wrong pair is a confusion with the word whose translation was chosen
+    pub fn add_pair_results(
         &mut self,
+        key: &ExerciseKey,
+        graded: &[(&str, Option<&str>, bool)],
+        hints: usize,
+    ) {
+        for &(word, chosen, correct) in graded {
+            if let (Some(chosen), false) = (chosen, correct) {
+                self.add_confusion(word, chosen);
+            }
+            self.add_result(word, key, correct, hints);
+        }
+    }
+
+    pub fn add_confusion(&mut self, word: &str, confused_with: &str) {
+        match self
+            .confusions
+            .iter_mut()
+            .find(|c| c.word == word && c.confused_with == confused_with)
+        {
+            Some(c) => c.count += 1,
+            None => self.confusions.push(Confusion {
+                word: word.to_owned(),
+                confused_with: confused_with.to_owned(),
+                count: 1,
+            }),
+        }
+    }
+
+    /// How many times the learner confused the words in any direction
+    pub fn confusion_count(&self, a: &str, b: &str) -> usize {
+        self.confusions
+            .iter()
+            .filter(|c| {
+                (c.word == a && c.confused_with == b) || (c.word == b && c.confused_with == a)
+            })
+            .map(|c| c.count)
+            .sum()
+    }
+
+    /// Pair of the word and the word it was confused with, the more confusions the more likely.
+    /// Both words have to be active and the first one in the selected groups
+    fn select_confusion(&self, db: &Database, rng: &mut StdRng) -> Option<(String, String)> {
+        let statuses = self.word_statuses();
+        let is_active = |word: &str| statuses.get(word) == Some(&WordStatus::Active);
+        let mut candidates: Vec<&Confusion> = self
+            .confusions
+            .iter()
+            .filter(|c| match db.words.get(&c.word) {
+                None => false,
+                Some(w) => self.is_word_in_groups(w),
+            })
+            .filter(|c| db.words.contains_key(&c.confused_with))
+            .filter(|c| is_active(&c.word) && is_active(&c.confused_with))
+            .collect();
+        if candidates.iter().any(|c| !self.is_recent(&c.word)) {
+            candidates.retain(|c| !self.is_recent(&c.word));
+        }
+        let chosen = candidates.choose_weighted(rng, |c| c.count).ok()?;
+        Some((chosen.word.clone(), chosen.confused_with.clone()))
+    }
+
+    fn select_word_to_learn(
+        &self,
+        db: &Database,
+        key: &ExerciseKey,
+        rng: &mut StdRng,
+    ) -> Option<usize> {
+        self.select_word_by_cmp(db, key, |_, _| true, &(), rng)
+    }
+
+    /// Index of the result of the word chosen by weighted random selection among the words
+    /// matching `cmp` in the selected groups, see `Scheduler` for the weights of the words.
+    /// Recently selected words are skipped unless there is nothing else to choose from.
+    /// Nothing is changed until the exercise with the word is created, see `word_selected`
+    fn select_word_by_cmp<T>(
+        &self,
         db: &Database,
-        cmp: impl Fn(&dyn Word, &T) -> bool,
+        key: &ExerciseKey,
+        cmp: impl Fn(&Word, &T) -> bool,
         prop: &T,
-    ) -> &mut ExerciseResults {
-        let mut rng = rand::thread_rng();
-        let mut weights = vec![];
+        rng: &mut StdRng,
+    ) -> Option<usize> {
         let mut indices = vec![];
-        for (i, &weight) in self.weights.iter().enumerate() {
-            let word = &self.results[i].word;
-            if let Some(w) = db.words.get(word) {
-                if cmp(w.as_ref(), prop) {
-                    weights.push(weight);
+        for (i, res) in self.results.iter().enumerate() {
+            if res.status != WordStatus::Active {
+                continue;
+            }
+            if let Some(w) = db.words.get(&res.word) {
+                if self.is_word_in_groups(w) && cmp(w, prop) {
                     indices.push(i);
                 }
             }
         }
-        let dist = WeightedIndex::new(weights).unwrap();
-        let idx = dist.sample(&mut rng);
-        &mut self.results[indices[idx]]
+        if self.new_words_limit.is_some() {
+            indices.retain(|&i| self.results[i].stage != WordStage::New);
+            let learning = self
+                .results
+                .iter()
+                .filter(|r| r.stage == WordStage::Learning)
+                .count();
+            if (indices.is_empty() || learning < MAX_LEARNING_WORDS) && self.can_introduce_word() {
+                if let Some(idx) = self.next_new_word(db, &cmp, prop) {
+                    return Some(idx);
+                }
+            }
+        }
+        if indices
+            .iter()
+            .any(|&i| !self.is_recent(&self.results[i].word))
+        {
+            indices.retain(|&i| !self.is_recent(&self.results[i].word));
+        }
+        let weights = self.selection_weights(&indices, key);
+        let dist = WeightedIndex::new(weights).ok()?;
+        Some(indices[dist.sample(rng)])
     }
 
-    fn select_word_by_pos(&mut self, db: &Database, pos: PartOfSpeech) -> &mut ExerciseResults {
-        let cmp = |word: &dyn Word, prop: &PartOfSpeech| &word.get_pos() == prop;
-        return self.select_word_by_cmp(db, cmp, &pos);
+    /// The exercise with the word is created: an unseen word is introduced
+    /// and the word is not repeated during the cooldown
+    fn word_selected(&mut self, word: &str) {
+        if let Some(idx) = self.results.iter().position(|r| r.word == word) {
+            if self.new_words_limit.is_some() && self.results[idx].stage == WordStage::New {
+                self.introduce_word(idx);
+            }
+        }
+        self.mark_recent(word);
+    }
+
+    fn selection_weights(&self, indices: &[usize], key: &ExerciseKey) -> Vec<f32> {
+        let weights: Vec<f32> = match self.scheduler {
+            Scheduler::Weights => {
+                let scores: Vec<_> = indices
+                    .iter()
+                    .map(|&i| self.results[i].exercise_score(key))
+                    .collect();
+                let max_score = scores.iter().copied().max().unwrap_or_default();
+                let min_score = scores.iter().copied().min().unwrap_or_default();
+                scores
+                    .iter()
+                    .map(|score| (2 * max_score - min_score - score + 1) as f32)
+                    .collect()
+            }
+            Scheduler::Leitner => indices
+                .iter()
+                .map(|&i| {
+                    let leitner_box = self.results[i].leitner_box.clamp(1, LEITNER_BOXES);
+                    LEITNER_BOX_FREQUENCY[leitner_box - 1]
+                })
+                .collect(),
+        };
+        if self.new_words_limit.is_none() {
+            return weights;
+        }
+        indices
+            .iter()
+            .zip(weights)
+            .map(|(&i, weight)| match self.results[i].stage {
+                WordStage::Learning => weight * LEARNING_WEIGHT_FACTOR,
+                _ => weight,
+            })
+            .collect()
+    }
+
+    fn select_word_by_pos(
+        &self,
+        db: &Database,
+        pos: PartOfSpeech,
+        rng: &mut StdRng,
+    ) -> Option<usize> {
+        let cmp = |word: &Word, prop: &PartOfSpeech| &word.get_pos() == prop;
+        let key = ExerciseKey::new(ExerciseType::GuessNounArticle);
+        self.select_word_by_cmp(db, &key, cmp, &pos, rng)
+    }
+
+    fn select_word_for_anagram(&self, db: &Database, rng: &mut StdRng) -> Option<usize> {
+        let cmp = |word: &Word, min_length: &usize| {
+            matches!(word.get_pos(), PartOfSpeech::Noun | PartOfSpeech::Verb)
+                && !word.get_word().contains(char::is_whitespace)
+                && word.get_word().chars().count() >= *min_length
+        };
+        let key = ExerciseKey::new(ExerciseType::Anagram);
+        self.select_word_by_cmp(db, &key, cmp, &ANAGRAM_MIN_LENGTH, rng)
+    }
+
+    fn select_word_with_example(&self, db: &Database, rng: &mut StdRng) -> Option<usize> {
+        let cmp = |word: &Word, _: &()| {
+            word.get_examples()
+                .iter()
+                .any(|e| cloze_sentence(e).is_some())
+        };
+        let key = ExerciseKey::new(ExerciseType::Cloze);
+        self.select_word_by_cmp(db, &key, cmp, &(), rng)
     }
 
     fn select_word_with_verb_form(
-        &mut self,
+        &self,
         db: &Database,
         form: &VerbFormExercise,
-    ) -> &mut ExerciseResults {
-        let cmp = |word: &dyn Word, form: &VerbFormExercise| {
+        rng: &mut StdRng,
+    ) -> Option<usize> {
+        let cmp = |word: &Word, form: &VerbFormExercise| {
             if word.get_pos() != PartOfSpeech::Verb {
                 return false;
             }
@@ -168,41 +893,75 @@ impl GameResults {
                     word.get_verb_perfect()
                 }
             };
-            match opt {
-                None => false,
-                Some(s) if s.is_empty() => false,
-                _ => true,
-            }
+            !matches!(opt, None | Some(""))
         };
-        return self.select_word_by_cmp(db, cmp, form);
+        let key = ExerciseKey::verb_form(*form);
+        self.select_word_by_cmp(db, &key, cmp, form, rng)
     }
+}
 
-    pub fn update_weights(&mut self) {
-        self.weights.clear();
-        self.results.sort_unstable();
-        let max_score = self.results.last().unwrap().score();
-        let min_score = self.results.first().unwrap().score();
-        self.weights.extend(
-            self.results
-                .iter()
-                .map(|ex| (2 * max_score - min_score - ex.score() + 1) as f32),
-        );
-        self.rand_dist = Some(WeightedIndex::new(&self.weights).unwrap());
+#[derive(Debug)]
+pub enum ExerciseError {
+    /// No word matches the exercise in the selected groups
+    NoWords(ExerciseType),
+    NotEnoughAnswerOptions(String),
+    MissingWordData(String, WordData),
+}
+
+/// Data of the word needed by an exercise
+#[derive(Debug, Clone, Copy)]
+pub enum WordData {
+    Article,
+    VerbForm,
+    Example,
+}
+
+impl ExerciseError {
+    /// Error text for the page in the UI language
+    pub fn message(&self) -> Message {
+        match self {
+            Self::NoWords(ex_type) => {
+                Message::new(MessageId::ErrorNoWords).with(format!("{:?}", ex_type))
+            }
+            Self::NotEnoughAnswerOptions(word) => {
+                Message::new(MessageId::ErrorNotEnoughAnswerOptions).with(word)
+            }
+            Self::MissingWordData(word, what) => {
+                let id = match what {
+                    WordData::Article => MessageId::ErrorNoArticle,
+                    WordData::VerbForm => MessageId::ErrorNoVerbForm,
+                    WordData::Example => MessageId::ErrorNoExample,
+                };
+                Message::new(id).with(word)
+            }
+        }
+    }
+}
+
+impl Display for ExerciseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message().format(UiLanguage::En))
     }
 }
 
+impl std::error::Error for ExerciseError {}
+
 pub struct ExerciseDataBullets {
     pub answers: Vec<String>,
     pub correct_idx: usize,
+    /// Words of the answer options, empty if the options are not words
+    pub words: Vec<String>,
 }
 
 pub struct ExerciseDataArticle {
     pub data: ExerciseDataBullets,
-    pub correct_message: String,
+    pub correct_message: Message,
 }
 
 pub struct ExerciseDataInput {
     pub correct_spelling: String,
+    /// Phrases are checked word by word, other words as typed
+    pub phrase: bool,
 }
 
 pub struct ExerciseDataVerbForm {
@@ -210,24 +969,120 @@ pub struct ExerciseDataVerbForm {
     data: ExerciseDataInput,
 }
 
+pub struct ExerciseDataConfusables {
+    pub data: ExerciseDataBullets,
+    pub correct_message: Message,
+}
+
+#[derive(Serialize, Clone, Tsify)]
+pub struct ExerciseDataPairs {
+    /// German words in the left column
+    pub words: Vec<String>,
+    /// Shuffled translations in the right column
+    pub translations: Vec<String>,
+    /// Index of the translation for every word
+    #[serde(skip)]
+    pub correct: Vec<usize>,
+    #[serde(skip)]
+    pub word_ids: Vec<String>,
+}
+
+/// How the page shows the exercise and takes the answer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Tsify)]
+#[serde(rename_all = "snake_case")]
+pub enum ExerciseKind {
+    /// One of the `options`, answered with `check_answer`
+    Choices,
+    /// One of the articles in `options`, answered with `check_answer`
+    Article,
+    /// Typed answer, answered with `check_answer_input`
+    Text,
+    /// Typed verb form, answered with `check_answer_input`
+    VerbForm,
+    /// Words matched with translations, answered with `check_answer_pairs`
+    Pairs,
+}
+
+#[derive(Serialize, Tsify)]
+pub struct PromptView {
+    /// Message id in snake case, see `MessageId`
+    pub id: String,
+    pub params: Vec<String>,
+    pub text: String,
+}
+
+impl PromptView {
+    fn new(message: &Message, lang: UiLanguage) -> Self {
+        Self {
+            id: message.id.as_ref().to_owned(),
+            params: message.format_params(lang),
+            text: message.format(lang),
+        }
+    }
+}
+
+/// Everything the page needs to show the exercise
+#[derive(Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct ExerciseView {
+    pub kind: ExerciseKind,
+    pub ex_type: ExerciseType,
+    pub prompt: PromptView,
+    /// Answer options of the choices and the articles, empty otherwise
+    pub options: Vec<String>,
+    pub pairs: Option<ExerciseDataPairs>,
+    /// How to type the answer in the text exercises
+    pub input_hint: Option<String>,
+    pub verb_form: Option<VerbFormExercise>,
+    pub word: String,
+    pub group: String,
+    pub pos: Option<PartOfSpeech>,
+}
+
+/// Result of checking the answer
+#[derive(Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct Verdict {
+    pub correct: bool,
+    /// Feedback in the UI language
+    pub message: String,
+    /// Spelling of the correct typed answer
+    pub correct_answer: Option<String>,
+    /// Grade of every pair of the matching exercise
+    pub pairs: Vec<bool>,
+}
+
 pub enum ExerciseData {
     Bullets(ExerciseDataBullets),
     TextInput(ExerciseDataInput),
     VerbForm(ExerciseDataVerbForm),
     Article(ExerciseDataArticle),
+    Confusables(ExerciseDataConfusables),
+    Pairs(ExerciseDataPairs),
 }
 
 pub struct Exercise {
     pub ex_type: ExerciseType,
-    pub task: String,
-    pub incorrect_message: String,
+    pub word: String,
+    pub task: Message,
+    pub incorrect_message: Message,
     pub data: ExerciseData,
 }
 
 impl Exercise {
+    pub fn key(&self) -> ExerciseKey {
+        match &self.data {
+            ExerciseData::VerbForm(data) => ExerciseKey::verb_form(data.form),
+            _ => ExerciseKey::new(self.ex_type),
+        }
+    }
+
     fn check_input_spelling(&self, input: &str) -> bool {
         if let ExerciseData::TextInput(data) = &self.data {
-            check_spelling_simple(input, &data.correct_spelling)
+            match data.phrase {
+                true => check_spelling_phrase(input, &data.correct_spelling),
+                false => check_spelling_simple(input, &data.correct_spelling),
+            }
         } else {
             false
         }
@@ -261,10 +1116,11 @@ impl Exercise {
         }
     }
 
-    pub fn get_correct_message(&self) -> &str {
+    pub fn get_correct_message(&self) -> Message {
         match &self.data {
-            ExerciseData::Article(data) => &data.correct_message,
-            _ => "Correct!",
+            ExerciseData::Article(data) => data.correct_message.clone(),
+            ExerciseData::Confusables(data) => data.correct_message.clone(),
+            _ => Message::new(MessageId::Correct),
         }
     }
 
@@ -272,250 +1128,594 @@ impl Exercise {
         match &self.data {
             ExerciseData::Article(data) => data.data.correct_idx == answer,
             ExerciseData::Bullets(data) => data.correct_idx == answer,
+            ExerciseData::Confusables(data) => data.data.correct_idx == answer,
             _ => false,
         }
     }
 
+    /// Word of the chosen answer option, if the options are words
+    pub fn get_answer_word(&self, answer: usize) -> Option<&str> {
+        match &self.data {
+            ExerciseData::Bullets(data) => data.words.get(answer).map(|w| w.as_str()),
+            ExerciseData::Confusables(data) => data.data.words.get(answer).map(|w| w.as_str()),
+            _ => None,
+        }
+    }
+
+    pub fn kind(&self) -> ExerciseKind {
+        match &self.data {
+            ExerciseData::Bullets(_) | ExerciseData::Confusables(_) => ExerciseKind::Choices,
+            ExerciseData::Article(_) => ExerciseKind::Article,
+            ExerciseData::TextInput(_) => ExerciseKind::Text,
+            ExerciseData::VerbForm(_) => ExerciseKind::VerbForm,
+            ExerciseData::Pairs(_) => ExerciseKind::Pairs,
+        }
+    }
+
+    pub fn view(&self, db: &Database, lang: UiLanguage) -> ExerciseView {
+        let word = db.words.get(&self.word);
+        let kind = self.kind();
+        ExerciseView {
+            kind,
+            ex_type: self.ex_type,
+            prompt: PromptView::new(&self.task, lang),
+            options: self.get_answers().cloned().unwrap_or_default(),
+            pairs: match &self.data {
+                ExerciseData::Pairs(data) => Some(data.clone()),
+                _ => None,
+            },
+            input_hint: matches!(kind, ExerciseKind::Text | ExerciseKind::VerbForm)
+                .then(|| Message::new(MessageId::TypeAnswer).format(lang)),
+            verb_form: self.key().verb_form,
+            word: self.word.clone(),
+            group: word
+                .and_then(|w| db.groups.get(w.get_group_id()))
+                .cloned()
+                .unwrap_or_default(),
+            pos: word.map(|w| w.get_pos()),
+        }
+    }
+
+    /// Grade every pair, `answer` holds the index of the chosen translation for every word.
+    /// Returns the word, the word of the chosen translation and if they match
+    pub fn check_pairs(&self, answer: &[usize]) -> Vec<(&str, Option<&str>, bool)> {
+        let data = match &self.data {
+            ExerciseData::Pairs(data) => data,
+            _ => return vec![],
+        };
+        data.word_ids
+            .iter()
+            .zip(data.correct.iter())
+            .enumerate()
+            .map(|(i, (word, &correct))| {
+                let chosen = answer.get(i).copied();
+                let chosen_word = chosen
+                    .and_then(|c| data.correct.iter().position(|&idx| idx == c))
+                    .map(|w| data.word_ids[w].as_str());
+                (word.as_str(), chosen_word, chosen == Some(correct))
+            })
+            .collect()
+    }
+
+    /// Hints for the typed answer: the first letter, then the length, then the article of a noun.
+    /// `None` when there are no more hints
+    pub fn get_hint(&self, word: &Word, level: usize) -> Option<Message> {
+        let answer = match &self.data {
+            ExerciseData::VerbForm(data) => data.data.correct_spelling.clone(),
+            // The gap may hold an inflected form of the word
+            ExerciseData::TextInput(data) if self.ex_type == ExerciseType::Cloze => {
+                data.correct_spelling.clone()
+            }
+            ExerciseData::TextInput(_) if word.get_pos() == PartOfSpeech::Noun => {
+                capitalize_noun(word.get_word())
+            }
+            ExerciseData::TextInput(_) => word.get_word().to_owned(),
+            _ => return None,
+        };
+        let first = answer.chars().next()?;
+        match level {
+            0 => Some(Message::new(MessageId::HintFirstLetter).with(first)),
+            1 => Some(
+                Message::new(MessageId::HintLength).with(
+                    std::iter::once(first)
+                        .chain(answer.chars().skip(1).map(|c| match c {
+                            ' ' => ' ',
+                            _ => '_',
+                        }))
+                        .map(|c| c.to_string())
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                ),
+            ),
+            2 => word
+                .get_article()
+                .map(|article| Message::new(MessageId::HintArticle).with(article.to_string())),
+            _ => None,
+        }
+    }
+
     pub fn get_answers(&self) -> Option<&Vec<String>> {
         match &self.data {
             ExerciseData::Article(data) => Some(&data.data.answers),
             ExerciseData::Bullets(data) => Some(&data.answers),
+            ExerciseData::Confusables(data) => Some(&data.data.answers),
             _ => None,
         }
     }
 }
 
-fn exercise_select_de(db: &Database, word: &dyn Word) -> Exercise {
-    let (options, correct_idx) = fetch_word_options(db, word);
+fn exercise_select_de(
+    db: &Database,
+    results: &GameResults,
+    word: &Word,
+    rng: &mut StdRng,
+) -> Result<Exercise, ExerciseError> {
+    let (options, correct_idx) = fetch_word_options(db, results, word, rng)?;
 
-    let task = format!(
-        "Select translation to Deutsch: {} ({})",
-        word.translation(),
-        word.pos_str()
-    );
+    let task = Message::new(MessageId::TaskSelectDe)
+        .with(word.translation())
+        .with_pos(word.get_pos());
 
     let answers: Vec<String> = options.iter().map(|w| w.spelling()).collect();
-    let incorrect_message = format!("Incorrect! The word is {}", word.spelling());
+    let incorrect_message = Message::new(MessageId::IncorrectWord).with(word.spelling());
     let data = ExerciseDataBullets {
         answers,
         correct_idx,
+        words: options.iter().map(|w| w.get_id().to_owned()).collect(),
     };
 
-    Exercise {
+    Ok(Exercise {
         ex_type: ExerciseType::SelectDe,
+        word: word.get_id().to_owned(),
         task,
         incorrect_message,
         data: ExerciseData::Bullets(data),
-    }
+    })
 }
 
-fn exercise_select_ru(db: &Database, word: &dyn Word) -> Exercise {
-    let (options, correct_idx) = fetch_word_options(db, word);
-
-    println!(
-        "Select translation to Russian: {} ({})",
-        word.spelling(),
-        word.pos_str()
-    );
+fn exercise_select_native(
+    db: &Database,
+    results: &GameResults,
+    word: &Word,
+    rng: &mut StdRng,
+) -> Result<Exercise, ExerciseError> {
+    let (options, correct_idx) = fetch_word_options(db, results, word, rng)?;
 
-    let task = format!(
-        "Select translation to Russian: {} ({})",
-        word.spelling(),
-        word.pos_str()
-    );
+    let task = Message::new(MessageId::TaskSelectNative)
+        .with_language(db.native_language())
+        .with(word.spelling())
+        .with_pos(word.get_pos());
 
     let answers: Vec<String> = options.iter().map(|w| w.translation().to_owned()).collect();
-    let incorrect_message = format!("Incorrect! The tranlation is {}", word.translation());
+    let incorrect_message = Message::new(MessageId::IncorrectTranslation).with(word.translation());
     let data = ExerciseDataBullets {
         answers,
         correct_idx,
+        words: options.iter().map(|w| w.get_id().to_owned()).collect(),
     };
 
-    Exercise {
-        ex_type: ExerciseType::SelectRu,
+    Ok(Exercise {
+        ex_type: ExerciseType::SelectNative,
+        word: word.get_id().to_owned(),
         task,
         incorrect_message,
         data: ExerciseData::Bullets(data),
-    }
+    })
 }
 
-fn exercise_translate_to_de(word: &dyn Word) -> Exercise {
-    let mut task = format!(
-        "Translate to German: {} ({})",
-        word.translation(),
-        word.pos_str()
-    );
+fn exercise_translate_to_de(word: &Word) -> Exercise {
     let help = word.get_help();
-    if !help.is_empty() {
-        task.push_str(&format!(". Hint: {}", help));
-    }
+    let task = match help.is_empty() {
+        true => Message::new(MessageId::TaskTranslateDe)
+            .with(word.translation())
+            .with_pos(word.get_pos()),
+        false => Message::new(MessageId::TaskTranslateDeHint)
+            .with(word.translation())
+            .with_pos(word.get_pos())
+            .with(help),
+    };
     let correct_spelling = word.spelling();
-    let incorrect_message = format!("Incorrect! The word is {}", word.spelling());
+    let incorrect_message = Message::new(MessageId::IncorrectWord).with(word.spelling());
 
     Exercise {
-        ex_type: ExerciseType::TranslateRuDe,
+        ex_type: ExerciseType::TranslateNativeDe,
+        word: word.get_id().to_owned(),
         task,
         incorrect_message,
-        data: ExerciseData::TextInput(ExerciseDataInput { correct_spelling }),
+        data: ExerciseData::TextInput(ExerciseDataInput {
+            correct_spelling,
+            phrase: word.get_pos() == PartOfSpeech::Phrase,
+        }),
     }
 }
 
-fn exercise_guess_noun_article(word: &dyn Word) -> Exercise {
-    let task = format!(
-        "Select the correct article for the noun: {}",
-        capitalize_noun(word.get_word())
-    );
+fn exercise_guess_noun_article(word: &Word) -> Result<Exercise, ExerciseError> {
+    let article = word.get_article().ok_or_else(|| {
+        ExerciseError::MissingWordData(word.get_word().to_owned(), WordData::Article)
+    })?;
+    let task = Message::new(MessageId::TaskArticle).with(capitalize_noun(word.get_word()));
 
     let answers: Vec<String> = NounArticle::iter().map(|a| a.answer_bullet_str()).collect();
-    let incorrect_message = format!(
-        "Incorrect! The article is {} - {}",
-        word.spelling(),
-        word.translation()
-    );
+    let incorrect_message = Message::new(MessageId::IncorrectArticle)
+        .with(word.spelling())
+        .with(word.translation());
 
-    let correct_idx = answers
-        .iter()
-        .enumerate()
-        .find_map(|(i, x)| {
-            if x == &word.get_article().unwrap().answer_bullet_str() {
-                Some(i)
-            } else {
-                None
-            }
-        })
-        .unwrap();
+    let correct_idx = NounArticle::iter()
+        .position(|a| a == article)
+        .unwrap_or_default();
     let data = ExerciseDataArticle {
         data: ExerciseDataBullets {
             answers,
             correct_idx,
+            words: vec![],
         },
-        correct_message: format!("Correct! {} - {}", word.spelling(), word.translation()),
+        correct_message: Message::new(MessageId::CorrectDetails).with(format!(
+            "{} - {}",
+            word.spelling(),
+            word.translation()
+        )),
     };
 
-    Exercise {
+    Ok(Exercise {
         ex_type: ExerciseType::GuessNounArticle,
+        word: word.get_id().to_owned(),
         task,
         incorrect_message,
         data: ExerciseData::Article(data),
-    }
+    })
 }
 
-fn exercise_verb_form(word: &dyn Word, form: VerbFormExercise) -> Exercise {
-    let task = format!(
-        "{} [ {} - {} ]",
-        match form {
-            VerbFormExercise::PresentThird => "Add verb in present tense: Er ... jetzt",
-            VerbFormExercise::Praeteritum => "Add verb in Präteritum : Er ... einst",
-            VerbFormExercise::Perfect => "Add verb in Perfekt : Er ... ... gestern",
-        },
-        word.get_word(),
-        word.translation()
-    );
+fn exercise_verb_form(word: &Word, form: VerbFormExercise) -> Result<Exercise, ExerciseError> {
+    let task = Message::new(match form {
+        VerbFormExercise::PresentThird => MessageId::TaskVerbPresentThird,
+        VerbFormExercise::Praeteritum => MessageId::TaskVerbPraeteritum,
+        VerbFormExercise::Perfect => MessageId::TaskVerbPerfect,
+    })
+    .with(word.get_word())
+    .with(word.translation());
 
     let correct_spelling = match form {
-        VerbFormExercise::PresentThird => word.get_verb_present_third().unwrap().to_owned(),
-        VerbFormExercise::Praeteritum => word.get_verb_praeteritum().unwrap().to_owned(),
-        VerbFormExercise::Perfect => word.get_verb_perfect_full().unwrap(),
-    };
-    let incorrect_message = format!("Incorrect! The form is {}", correct_spelling);
+        VerbFormExercise::PresentThird => word.get_verb_present_third().map(|s| s.to_owned()),
+        VerbFormExercise::Praeteritum => word.get_verb_praeteritum().map(|s| s.to_owned()),
+        VerbFormExercise::Perfect => word.get_verb_perfect_full(),
+    }
+    .ok_or_else(|| {
+        ExerciseError::MissingWordData(word.get_word().to_owned(), WordData::VerbForm)
+    })?;
+    let incorrect_message = Message::new(MessageId::IncorrectForm).with(&correct_spelling);
     let data = ExerciseDataVerbForm {
-        data: ExerciseDataInput { correct_spelling },
+        data: ExerciseDataInput {
+            correct_spelling,
+            phrase: false,
+        },
         form,
     };
 
-    Exercise {
+    Ok(Exercise {
         ex_type: ExerciseType::VerbFormRandom,
+        word: word.get_id().to_owned(),
         task,
         incorrect_message,
         data: ExerciseData::VerbForm(data),
+    })
+}
+
+/// The word and the word it was confused with side by side
+fn exercise_confusables(word: &Word, other: &Word, rng: &mut StdRng) -> Exercise {
+    let task = Message::new(MessageId::TaskConfusables)
+        .with(word.translation())
+        .with_pos(word.get_pos());
+
+    let mut options = [word, other];
+    options.shuffle(rng);
+    let correct_idx = options
+        .iter()
+        .position(|w| w.get_id() == word.get_id())
+        .unwrap_or_default();
+    let both = format!(
+        "{} - {}, {} - {}",
+        word.spelling(),
+        word.translation(),
+        other.spelling(),
+        other.translation()
+    );
+    let data = ExerciseDataConfusables {
+        data: ExerciseDataBullets {
+            answers: options.iter().map(|w| w.spelling()).collect(),
+            correct_idx,
+            words: options.iter().map(|w| w.get_id().to_owned()).collect(),
+        },
+        correct_message: Message::new(MessageId::CorrectDetails).with(&both),
+    };
+
+    Exercise {
+        ex_type: ExerciseType::Confusables,
+        word: word.get_id().to_owned(),
+        task,
+        incorrect_message: Message::new(MessageId::Incorrect).with(both),
+        data: ExerciseData::Confusables(data),
     }
 }
 
-pub fn exercise_verb_form_random(word: &dyn Word) -> Exercise {
-    let mut rng = rand::thread_rng();
-    let form = VerbFormExercise::iter().choose(&mut rng).unwrap();
-    exercise_verb_form(word, form)
+/// Translation and the shuffled letters of the word, the article of a noun is given
+fn exercise_anagram(word: &Word, rng: &mut StdRng) -> Exercise {
+    let correct_spelling = match word.get_pos() {
+        PartOfSpeech::Noun => capitalize_noun(word.get_word()),
+        _ => word.get_word().to_owned(),
+    };
+    let mut letters: Vec<char> = correct_spelling.to_lowercase().chars().collect();
+    let original = letters.clone();
+    // Words like "essen" may shuffle into themselves
+    for _ in 0..ANAGRAM_SHUFFLE_TRIES {
+        letters.shuffle(rng);
+        if letters != original {
+            break;
+        }
+    }
+    let letters: Vec<String> = letters.iter().map(|c| c.to_string()).collect();
+
+    let task = match word.get_article() {
+        Some(article) => Message::new(MessageId::TaskAnagramArticle)
+            .with(letters.join(" "))
+            .with(word.translation())
+            .with(article.to_string()),
+        None => Message::new(MessageId::TaskAnagram)
+            .with(letters.join(" "))
+            .with(word.translation()),
+    };
+    let incorrect_message = Message::new(MessageId::IncorrectWord).with(word.spelling());
+
+    Exercise {
+        ex_type: ExerciseType::Anagram,
+        word: word.get_id().to_owned(),
+        task,
+        incorrect_message,
+        data: ExerciseData::TextInput(ExerciseDataInput {
+            correct_spelling,
+            phrase: word.get_pos() == PartOfSpeech::Phrase,
+        }),
+    }
 }
 
-fn fetch_word_options<'a>(db: &'a Database, word: &'a dyn Word) -> (Vec<&'a dyn Word>, usize) {
-    let group_id = word.get_group_id();
-    let pos = word.get_pos();
-    let mut rng = rand::thread_rng();
-    let candidates: Vec<_> = db
-        .words
+/// Example sentence of the word with a gap in place of the word
+fn exercise_cloze(word: &Word, rng: &mut StdRng) -> Result<Exercise, ExerciseError> {
+    let examples: Vec<_> = word
+        .get_examples()
         .iter()
-        .filter_map(|(_, w)| {
-            if w.get_group_id() == group_id && w.get_pos() == pos {
-                Some(w)
-            } else {
-                None
-            }
-        })
+        .filter_map(|e| cloze_sentence(e).map(|cloze| (e, cloze)))
         .collect();
+    let (example, (sentence, correct_spelling)) = examples.choose(rng).ok_or_else(|| {
+        ExerciseError::MissingWordData(word.get_word().to_owned(), WordData::Example)
+    })?;
 
-    let mut options = vec![];
-    let mut used_words = HashSet::new();
-    options.push(word);
-    used_words.insert(word.get_word());
-
-    const MAX_ATTEMPTS: usize = 1000;
-    let mut attempts = 0usize;
-    while options.len() < ANSWER_OPTIONS.min(candidates.len()) {
-        let cand = &***candidates.choose(&mut rng).unwrap();
-        if !used_words.contains(cand.get_word()) {
-            used_words.insert(cand.get_word());
-            options.push(cand);
-        } else {
-            attempts += 1;
-            if attempts >= MAX_ATTEMPTS {
-                panic!("Cannot choose answer options");
-            }
+    let task = Message::new(MessageId::TaskCloze)
+        .with(sentence)
+        .with(word.get_word())
+        .with(word.translation());
+    let incorrect_message = Message::new(MessageId::Incorrect).with(example.replace('*', ""));
+
+    Ok(Exercise {
+        ex_type: ExerciseType::Cloze,
+        word: word.get_id().to_owned(),
+        task,
+        incorrect_message,
+        data: ExerciseData::TextInput(ExerciseDataInput {
+            correct_spelling: correct_spelling.to_owned(),
+            phrase: word.get_pos() == PartOfSpeech::Phrase,
+        }),
+    })
+}
+
+/// Active words of the group to be matched with their translations
+fn exercise_match_pairs(
+    db: &Database,
+    results: &GameResults,
+    word: &Word,
+    rng: &mut StdRng,
+) -> Result<Exercise, ExerciseError> {
+    let statuses = results.word_statuses();
+    let mut candidates: Vec<&Word> = db
+        .words_in_order()
+        .filter(|w| w.get_group_id() == word.get_group_id() && w.get_id() != word.get_id())
+        .filter(|w| statuses.get(w.get_id()) == Some(&WordStatus::Active))
+        .collect();
+    candidates.shuffle(rng);
+
+    let amount = rng.gen_range(MATCH_PAIRS_MIN..=MATCH_PAIRS_MAX);
+    let mut pairs = vec![word];
+    // Same translation or spelling would make the matching ambiguous
+    for candidate in candidates {
+        if pairs.len() == amount {
+            break;
         }
+        if pairs.iter().all(|w| {
+            w.translation() != candidate.translation() && w.spelling() != candidate.spelling()
+        }) {
+            pairs.push(candidate);
+        }
+    }
+    if pairs.len() < MATCH_PAIRS_MIN {
+        return Err(ExerciseError::NotEnoughAnswerOptions(
+            word.get_word().to_owned(),
+        ));
+    }
+    pairs.shuffle(rng);
+
+    let mut order: Vec<usize> = (0..pairs.len()).collect();
+    order.shuffle(rng);
+    let mut correct = vec![0; pairs.len()];
+    for (idx, &w) in order.iter().enumerate() {
+        correct[w] = idx;
     }
+    let data = ExerciseDataPairs {
+        words: pairs.iter().map(|w| w.spelling()).collect(),
+        translations: order
+            .iter()
+            .map(|&w| pairs[w].translation().to_owned())
+            .collect(),
+        correct,
+        word_ids: pairs.iter().map(|w| w.get_id().to_owned()).collect(),
+    };
+    let incorrect_message = Message::new(MessageId::Incorrect).with(
+        pairs
+            .iter()
+            .map(|w| format!("{} - {}", w.spelling(), w.translation()))
+            .collect::<Vec<_>>()
+            .join(", "),
+    );
+
+    Ok(Exercise {
+        ex_type: ExerciseType::MatchPairs,
+        word: word.get_id().to_owned(),
+        task: Message::new(MessageId::TaskMatchPairs),
+        incorrect_message,
+        data: ExerciseData::Pairs(data),
+    })
+}
 
-    let mut indices = (0..options.len()).collect::<Vec<usize>>();
-    indices.shuffle(&mut rng);
-    let mut correct = 0usize;
+/// How likely the learner confuses the candidate with the word, higher is more confusable
+fn distractor_weight(results: &GameResults, word: &Word, candidate: &Word) -> f64 {
+    let a = word.get_word().to_lowercase();
+    let b = candidate.get_word().to_lowercase();
+    let (len_a, len_b) = (a.chars().count(), b.chars().count());
+    let max_len = len_a.max(len_b).max(1) as f64;
 
-    let mut opt = vec![];
-    for idx in indices {
-        opt.push(options[idx]);
-        if idx == 0 {
-            correct = opt.len() - 1;
-        }
+    let spelling = 1.0 - edit_distance(&a, &b) as f64 / max_len;
+    let length = 1.0 - len_a.abs_diff(len_b) as f64 / max_len;
+    let article = match (word.get_article(), candidate.get_article()) {
+        (Some(x), Some(y)) if x == y => 1.0,
+        _ => 0.0,
+    };
+    let confusions = results.confusion_count(word.get_id(), candidate.get_id()) as f64;
+
+    DISTRACTOR_BASE_WEIGHT
+        + spelling * DISTRACTOR_SPELLING_WEIGHT
+        + length * DISTRACTOR_LENGTH_WEIGHT
+        + article * DISTRACTOR_ARTICLE_WEIGHT
+        + confusions * DISTRACTOR_CONFUSION_WEIGHT
+}
+
+fn choose_distractors<'a>(
+    candidates: &[&'a Word],
+    amount: usize,
+    weight: impl Fn(&&'a Word) -> f64,
+    rng: &mut StdRng,
+) -> Vec<&'a Word> {
+    match candidates.choose_multiple_weighted(rng, amount, weight) {
+        Ok(chosen) => chosen.copied().collect(),
+        Err(_) => candidates.choose_multiple(rng, amount).copied().collect(),
+    }
+}
+
+fn fetch_word_options<'a>(
+    db: &'a Database,
+    results: &GameResults,
+    word: &'a Word,
+    rng: &mut StdRng,
+) -> Result<(Vec<&'a Word>, usize), ExerciseError> {
+    let group_id = word.get_group_id();
+    let pos = word.get_pos();
+    let statuses = results.word_statuses();
+    let (same_group, other_groups): (Vec<_>, Vec<_>) = db
+        .words_in_order()
+        .filter(|w| w.get_pos() == pos && w.get_id() != word.get_id())
+        .filter(|w| statuses.get(w.get_id()) != Some(&WordStatus::Suspended))
+        .partition(|w| w.get_group_id() == group_id);
+
+    let weight = |w: &&Word| distractor_weight(results, word, w);
+    let mut options = choose_distractors(&same_group, ANSWER_OPTIONS - 1, weight, rng);
+    // Small groups are completed with the words from the other groups
+    let missing = ANSWER_OPTIONS - 1 - options.len();
+    options.extend(choose_distractors(&other_groups, missing, weight, rng));
+    if options.is_empty() {
+        return Err(ExerciseError::NotEnoughAnswerOptions(
+            word.get_word().to_owned(),
+        ));
     }
-    (opt, correct)
+
+    options.push(word);
+    options.shuffle(rng);
+    let correct = options
+        .iter()
+        .position(|w| w.get_id() == word.get_id())
+        .unwrap_or_default();
+    Ok((options, correct))
 }
 
 pub fn create_exercise_with_type(
     db: &Database,
     results: &mut GameResults,
     ex_type: &ExerciseType,
-) -> Option<Exercise> {
-    let exercise_result = match ex_type {
-        ExerciseType::VerbFormRandom => {
-            let mut rng = rand::thread_rng();
-            let form = VerbFormExercise::iter().choose(&mut rng).unwrap();
-            results.select_word_with_verb_form(db, &form)
-        }
-        ExerciseType::GuessNounArticle => results.select_word_by_pos(db, PartOfSpeech::Noun),
-        _ => results.select_word_to_learn(),
-    };
-    let word = match db.words.get(&exercise_result.word) {
-        Some(w) => &**w,
-        None => {
-            return None;
-        }
+    rng: &mut StdRng,
+) -> Result<Exercise, ExerciseError> {
+    let mut verb_forms: Vec<_> = VerbFormExercise::iter().collect();
+    verb_forms.shuffle(rng);
+    let mut verb_form = verb_forms[0];
+    let mut confused_with = None;
+    let word_of = |idx: usize| results.results[idx].word.clone();
+    let selected = match ex_type {
+        // Try the other forms if no verb has the chosen one
+        ExerciseType::VerbFormRandom => verb_forms
+            .iter()
+            .find_map(|&form| {
+                verb_form = form;
+                results.select_word_with_verb_form(db, &form, rng)
+            })
+            .map(word_of),
+        ExerciseType::GuessNounArticle => results
+            .select_word_by_pos(db, PartOfSpeech::Noun, rng)
+            .map(word_of),
+        ExerciseType::Anagram => results.select_word_for_anagram(db, rng).map(word_of),
+        ExerciseType::Cloze => results.select_word_with_example(db, rng).map(word_of),
+        ExerciseType::Confusables => results.select_confusion(db, rng).map(|(word, other)| {
+            confused_with = db.words.get(&other);
+            word
+        }),
+        _ => results
+            .select_word_to_learn(db, &ExerciseKey::new(*ex_type), rng)
+            .map(word_of),
     };
+    // The selection changes nothing until the exercise is created
+    let word = selected
+        .and_then(|w| db.words.get(&w))
+        .ok_or(ExerciseError::NoWords(*ex_type))?;
 
-    let ex = match ex_type {
-        ExerciseType::SelectDe => exercise_select_de(db, word),
-        ExerciseType::TranslateRuDe => exercise_translate_to_de(word),
-        ExerciseType::SelectRu => exercise_select_ru(db, word),
+    let exercise = match ex_type {
+        ExerciseType::SelectDe => exercise_select_de(db, results, word, rng),
+        ExerciseType::TranslateNativeDe => Ok(exercise_translate_to_de(word)),
+        ExerciseType::SelectNative => exercise_select_native(db, results, word, rng),
         ExerciseType::GuessNounArticle => exercise_guess_noun_article(word),
-        ExerciseType::VerbFormRandom => exercise_verb_form_random(word),
-        _ => return None,
-    };
+        ExerciseType::VerbFormRandom => exercise_verb_form(word, verb_form),
+        ExerciseType::Confusables => match confused_with {
+            Some(other) => Ok(exercise_confusables(word, other, rng)),
+            None => Err(ExerciseError::NoWords(*ex_type)),
+        },
+        ExerciseType::MatchPairs => exercise_match_pairs(db, results, word, rng),
+        ExerciseType::Anagram => Ok(exercise_anagram(word, rng)),
+        ExerciseType::Cloze => exercise_cloze(word, rng),
+    }?;
+    results.word_selected(word.get_id());
+    Ok(exercise)
+}
 
-    Some(ex)
+/// Exercise of the planned type or, if it cannot be created, of the first possible fallback type.
+/// The error of the planned type is returned when none of them can be created
+pub fn create_exercise_with_fallback(
+    db: &Database,
+    results: &mut GameResults,
+    planned: ExerciseType,
+    fallbacks: &[ExerciseType],
+    rng: &mut StdRng,
+) -> Result<Exercise, ExerciseError> {
+    let mut error = None;
+    for ex_type in std::iter::once(&planned).chain(fallbacks) {
+        match create_exercise_with_type(db, results, ex_type, rng) {
+            Ok(ex) => return Ok(ex),
+            Err(e) => {
+                log::warn!("Cannot create exercise {:?}: {}", ex_type, e);
+                error.get_or_insert(e);
+            }
+        }
+    }
+    Err(error.unwrap_or(ExerciseError::NoWords(planned)))
 }
@@ -1,10 +1,18 @@
 use base64::Engine;
 use calamine::Reader;
 use core::panic;
-use std::collections::HashMap;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::collections::HashSet;
+use std::str::FromStr;
+use strum::IntoEnumIterator;
 use wasm_bindgen::prelude::*;
 
 use crate::exercise::*;
+use crate::messages::{Message, MessageId, UiLanguage};
+use crate::session::*;
+use crate::statistics::{collect_statistics, Statistics};
+use crate::utils::{hash_str, now, today};
 use crate::words::*;
 
 #[wasm_bindgen]
@@ -17,23 +25,86 @@ pub struct WordsGame {
     db: Database,
     results: GameResults,
     exercise: Option<Exercise>,
+    exercise_answered: bool,
+    hints_used: usize,
 
-    exercise_number: usize,
-    exercise_type: ExerciseType,
+    session: Session,
+    rng: StdRng,
+    daily_challenge: bool,
+    ui_language: UiLanguage,
 }
 
-const EXERCISE_SAME_TYPE_COUNT: usize = 10;
-
 #[wasm_bindgen]
 impl WordsGame {
     pub fn create() -> WordsGame {
-        WordsGame {
-            db: Database::new(),
-            results: GameResults::new(),
-            exercise: None,
-            exercise_number: 0,
-            exercise_type: ExerciseType::SelectDe,
+        Self::create_with_rng(StdRng::from_entropy())
+    }
+
+    /// Game with reproducible exercises for the given seed and vocabulary
+    pub fn create_with_seed(seed: u32) -> WordsGame {
+        Self::create_with_rng(StdRng::seed_from_u64(seed as u64))
+    }
+
+    /// Daily challenge: the same exercises for everyone for the given date.
+    /// Answers are not recorded to the results, so they do not affect the next exercises
+    pub fn create_daily_challenge(date: &str) -> WordsGame {
+        let mut game = Self::create_with_rng(StdRng::seed_from_u64(hash_str(date)));
+        game.session = Session::new(SessionConfig::daily_challenge()).unwrap();
+        game.daily_challenge = true;
+        game
+    }
+
+    pub fn is_daily_challenge(&self) -> bool {
+        self.daily_challenge
+    }
+
+    /// Start a new session with the given plan
+    pub fn set_session_config(&mut self, config: SessionConfig) -> Result<(), JsError> {
+        self.session = Session::new(config).map_err(|e| JsError::new(&e))?;
+        self.results.set_scheduler(self.session.scheduler());
+        self.results.start_session();
+        self.exercise = None;
+        Ok(())
+    }
+
+    pub fn is_session_finished(&self) -> bool {
+        self.session.is_finished()
+    }
+
+    pub fn get_session_summary(&self) -> SessionSummary {
+        self.session.summary()
+    }
+
+    /// Session and all-time statistics
+    pub fn get_statistics(&self) -> Statistics {
+        collect_statistics(&self.db, &self.results, &self.session)
+    }
+
+    /// Number of words in each Leitner box
+    pub fn get_leitner_boxes(&self) -> Vec<u32> {
+        self.results
+            .get_leitner_boxes(&self.db)
+            .iter()
+            .map(|&n| n as u32)
+            .collect()
+    }
+
+    /// All results as JSON to be stored by the page and passed to `import_results` later
+    pub fn export_results(&self) -> String {
+        self.results.to_json()
+    }
+
+    pub fn import_results(&mut self, results: &str) -> Result<(), JsError> {
+        if self.daily_challenge {
+            return Err(JsError::new("Results are not used in the daily challenge"));
         }
+        self.results
+            .load_json(results)
+            .map_err(|e| JsError::new(&e))?;
+        if !self.db.words.is_empty() {
+            self.update_results();
+        }
+        Ok(())
     }
 
     pub async fn fetch_words(&mut self) -> Result<usize, JsError> {
@@ -86,119 +157,330 @@ impl WordsGame {
         let rows_count = range.rows().count();
         log::info!("Parsed xlsx with {} words", rows_count);
 
-        for row in range.rows().skip(2) {
-            let mut map: HashMap<usize, String> =
-                row.iter().map(|dt| dt.to_string()).enumerate().collect();
-            let pos = get_part_of_speech(&map);
-            let word = match pos {
-                "n" => Box::new(Noun::new(&mut map, &mut self.db)) as Box<dyn Word>,
-                "v" => Box::new(Verb::new(&mut map, &mut self.db)),
-                "adj" => Box::new(Adjective::new(&mut map, &mut self.db)),
-                "adv" => Box::new(Adverb::new(&mut map, &mut self.db)),
-                "prep" => Box::new(Preposition::new(&mut map, &mut self.db)),
-                _ => continue,
-            };
-            self.db.words.insert(word.get_word().to_owned(), word);
-        }
-
-        self.results.update_with_db(&self.db);
-        self.results.update_weights();
-
-        Ok(self.db.words.keys().count())
-    }
-
-    pub fn create_exercise(&mut self) -> bool {
-        self.exercise = create_exercise_with_type(&self.db, &mut self.results, &self.exercise_type);
-
-        if self.exercise.is_some() {
-            self.exercise_number += 1;
-            if self.exercise_number == EXERCISE_SAME_TYPE_COUNT {
-                self.exercise_number = 0;
-                self.exercise_type = match self.exercise_type {
-                    ExerciseType::SelectDe => ExerciseType::TranslateRuDe,
-                    ExerciseType::TranslateRuDe => ExerciseType::SelectRu,
-                    ExerciseType::SelectRu => ExerciseType::GuessNounArticle,
-                    ExerciseType::GuessNounArticle => ExerciseType::VerbFormRandom,
-                    _ => ExerciseType::SelectDe,
-                };
+        self.db.read_sheet(&range);
+        Ok(self.words_loaded())
+    }
+
+    /// Load the vocabulary preprocessed to JSON, see `VocabularyJson`, instead of `fetch_words`
+    pub fn load_words_json(&mut self, json: &str) -> Result<usize, JsError> {
+        self.db = Database::from_json(json).map_err(|e| JsError::new(&e))?;
+        Ok(self.words_loaded())
+    }
+
+    /// Vocabulary as JSON to be loaded with `load_words_json`
+    pub fn export_words_json(&self) -> String {
+        self.db.to_json()
+    }
+
+    fn words_loaded(&mut self) -> usize {
+        for warning in self.db.warnings.iter() {
+            log::warn!("{}", warning);
+        }
+        self.update_results();
+        self.db.words.len()
+    }
+
+    /// Problems with the saved results are reported together with the vocabulary warnings
+    fn update_results(&mut self) {
+        for warning in self.results.update_with_db(&self.db) {
+            if !self.db.warnings.contains(&warning) {
+                log::warn!("{}", warning);
+                self.db.warnings.push(warning);
             }
         }
-        self.exercise.is_some()
     }
 
-    pub fn get_answers(&self) -> JsValue {
-        let ex = match &self.exercise {
-            None => return JsValue::UNDEFINED,
-            Some(ex) => ex,
-        };
+    /// Codes of the languages with translations, the first one is the default
+    pub fn get_languages(&self) -> Vec<String> {
+        self.db.languages.clone()
+    }
 
-        let answers = match ex.get_answers() {
-            Some(a) => a,
-            _ => return JsValue::UNDEFINED,
-        };
+    /// Translate to the native language of the learner given by its code, e.g. "en".
+    /// Call after `fetch_words`, the vocabulary defines the available languages
+    pub fn set_native_language(&mut self, code: &str) -> Result<(), JsError> {
+        self.db
+            .set_native_language(code)
+            .map_err(|e| JsError::new(&e))
+    }
 
-        return JsValue::from(
-            answers
-                .iter()
-                .map(|x| JsValue::from_str(x))
-                .collect::<js_sys::Array>(),
-        );
+    pub fn get_native_language(&self) -> String {
+        self.db.native_language().to_owned()
     }
 
-    pub fn get_task(&self) -> JsValue {
-        match &self.exercise {
-            None => JsValue::UNDEFINED,
-            Some(ex) => JsValue::from_str(&ex.task),
+    /// Language of the interface and the exercises: "en", "de" or "ru"
+    pub fn set_ui_language(&mut self, code: &str) -> Result<(), JsError> {
+        self.ui_language = UiLanguage::from_str(code)?;
+        Ok(())
+    }
+
+    pub fn get_ui_language(&self) -> String {
+        self.ui_language.as_ref().to_owned()
+    }
+
+    /// Text of the interface in the UI language, e.g. `get_message("session_result", ["3", "5"])`
+    pub fn get_message(&self, id: &str, params: Vec<String>) -> Result<String, JsError> {
+        let message = params
+            .into_iter()
+            .fold(Message::new(MessageId::from_str(id)?), |m, p| m.with(p));
+        Ok(message.format(self.ui_language))
+    }
+
+    /// Problems found in the vocabulary, e.g. duplicate words
+    pub fn get_warnings(&self) -> Vec<String> {
+        self.db.warnings.clone()
+    }
+
+    /// Words of the vocabulary matching the query
+    pub fn search_words(&self, query: WordQuery) -> WordSearch {
+        WordSearch {
+            words: self
+                .db
+                .search(&query)
+                .into_iter()
+                .map(|w| WordCard::new(&self.db, w))
+                .collect(),
         }
     }
 
-    pub fn check_answer(&self, answer: usize) -> bool {
-        match &self.exercise {
-            None => false,
-            Some(ex) => ex.check_answer(answer)
+    /// Card of the word by its id, see `WordCard::id`
+    pub fn get_word_card(&self, word: &str) -> Option<WordCard> {
+        let word = self.db.words.get(word)?;
+        Some(WordCard::new(&self.db, word))
+    }
+
+    /// Groups of the vocabulary with the number of words in each
+    pub fn get_groups(&self) -> GroupList {
+        GroupList {
+            groups: self.db.get_groups_info(),
+        }
+    }
+
+    /// Practice only the groups with the given ids, no ids to practice all groups
+    pub fn set_groups(&mut self, groups: &[u32]) -> Result<(), JsError> {
+        let groups: HashSet<usize> = groups.iter().map(|&id| id as usize).collect();
+        if let Some(id) = groups.iter().find(|&&id| id >= self.db.groups.len()) {
+            return Err(JsError::new(&format!("Unknown group id {}", id)));
         }
+        self.results
+            .set_groups((!groups.is_empty()).then_some(groups));
+        Ok(())
+    }
+
+    /// Practice only the most recently added group, returns its name
+    pub fn practice_latest_group(&mut self) -> Option<String> {
+        let id = self.db.get_latest_group_id()?;
+        self.results.set_groups(Some(HashSet::from([id])));
+        Some(self.db.groups[id].clone())
+    }
+
+    /// Set the status of the word by its id: "Active", "Mastered" or "Suspended"
+    pub fn set_word_status(&mut self, word: &str, status: &str) -> Result<(), JsError> {
+        let status = WordStatus::from_str(status)?;
+        if !self.results.set_word_status(word, status) {
+            return Err(JsError::new(&format!("Unknown word {}", word)));
+        }
+        Ok(())
+    }
+
+    /// Ids of the words with the given status
+    pub fn get_words_with_status(&self, status: &str) -> Result<Vec<String>, JsError> {
+        let status = WordStatus::from_str(status)?;
+        Ok(self
+            .results
+            .get_words_with_status(status)
+            .into_iter()
+            .map(str::to_owned)
+            .collect())
+    }
+
+    /// Do not repeat a word for the given number of exercises
+    pub fn set_cooldown_exercises(&mut self, exercises: usize) {
+        self.results.set_cooldown(Cooldown::Exercises(exercises));
+    }
+
+    /// Do not repeat a word for the given number of seconds
+    pub fn set_cooldown_seconds(&mut self, seconds: f64) {
+        self.results.set_cooldown(Cooldown::Seconds(seconds));
+    }
+
+    /// Introduce at most the given number of unseen words per day
+    pub fn set_new_words_per_day(&mut self, words: usize) {
+        self.results
+            .set_new_words_limit(Some(NewWordsLimit::PerDay(words)));
     }
 
-    pub fn get_incorrent_message(&self) -> JsValue {
-        match &self.exercise {
-            None => JsValue::UNDEFINED,
-            Some(ex) => JsValue::from_str(&ex.incorrect_message),
+    /// Introduce at most the given number of unseen words per session
+    pub fn set_new_words_per_session(&mut self, words: usize) {
+        self.results
+            .set_new_words_limit(Some(NewWordsLimit::PerSession(words)));
+    }
+
+    /// Learn all words of the vocabulary at once
+    pub fn clear_new_words_limit(&mut self) {
+        self.results.set_new_words_limit(None);
+    }
+
+    /// Returns nothing when the session is finished
+    /// and an error if no exercise of the enabled types can be created
+    pub fn create_exercise(&mut self) -> Result<Option<ExerciseView>, JsError> {
+        self.exercise = None;
+        self.exercise_answered = false;
+        self.hints_used = 0;
+        self.results.set_clock(now(), &today());
+        let planned = match self.session.next_type(&mut self.rng) {
+            Some(t) => t,
+            None => return Ok(None),
+        };
+        // Selected groups may have no words for some exercise types, try the other enabled ones
+        let fallbacks = ExerciseType::iter()
+            .filter(|&t| t != planned && self.session.is_type_enabled(t))
+            .collect::<Vec<_>>();
+        match create_exercise_with_fallback(
+            &self.db,
+            &mut self.results,
+            planned,
+            &fallbacks,
+            &mut self.rng,
+        ) {
+            Ok(ex) => {
+                self.exercise = Some(ex);
+                self.session.exercise_created();
+                Ok(self.get_exercise())
+            }
+            Err(e) => Err(JsError::new(&e.message().format(self.ui_language))),
         }
     }
 
-    pub fn is_exercise_input(&self) -> bool {
-        match &self.exercise {
+    /// The current exercise in the current UI language
+    pub fn get_exercise(&self) -> Option<ExerciseView> {
+        Some(self.exercise.as_ref()?.view(&self.db, self.ui_language))
+    }
+
+    pub fn check_answer(&mut self, answer: usize) -> Verdict {
+        let correct = match &self.exercise {
             None => false,
-            Some(ex) => match ex.ex_type {
-                ExerciseType::TranslateRuDe | ExerciseType::VerbFormRandom => true,
-                _ => false,
-            },
+            Some(ex) => ex.check_answer(answer),
+        };
+        if !correct && !self.exercise_answered && !self.daily_challenge {
+            if let Some(ex) = &self.exercise {
+                if let Some(chosen) = ex.get_answer_word(answer) {
+                    self.results.add_confusion(&ex.word, chosen);
+                }
+            }
         }
+        self.record_result(correct);
+        self.verdict(correct, vec![])
     }
 
-    pub fn check_answer_input(&self, answer: &str) -> bool {
-        if let Some(ex) = &self.exercise {
+    /// Index of the chosen translation for every word of the matching exercise,
+    /// the verdict grades each pair
+    pub fn check_answer_pairs(&mut self, answer: &[u32]) -> Verdict {
+        let answer: Vec<usize> = answer.iter().map(|&a| a as usize).collect();
+        let ex = match &self.exercise {
+            None => return self.verdict(false, vec![]),
+            Some(ex) => ex,
+        };
+        let graded = ex.check_pairs(&answer);
+        if !self.exercise_answered {
+            let key = ex.key();
+            if !self.daily_challenge {
+                self.results
+                    .add_pair_results(&key, &graded, self.hints_used);
+            }
+            for (word, _, correct) in graded.iter() {
+                self.session.add_result(word, &key, *correct);
+            }
+            self.exercise_answered = true;
+        }
+        let pairs: Vec<bool> = graded.iter().map(|(_, _, correct)| *correct).collect();
+        self.verdict(!pairs.is_empty() && pairs.iter().all(|&c| c), pairs)
+    }
+
+    /// Next hint for the typed answer, every hint reduces the credit for the correct answer
+    pub fn request_hint(&mut self) -> Option<String> {
+        let hint = self.exercise.as_ref().and_then(|ex| {
+            let word = self.db.words.get(&ex.word)?;
+            ex.get_hint(word, self.hints_used)
+        })?;
+        if !self.exercise_answered {
+            self.hints_used += 1;
+        }
+        Some(hint.format(self.ui_language))
+    }
+
+    pub fn check_answer_input(&mut self, answer: &str) -> Verdict {
+        let correct = if let Some(ex) = &self.exercise {
             ex.check_spelling(answer)
         } else {
             false
+        };
+        self.record_result(correct);
+        self.verdict(correct, vec![])
+    }
+}
+
+impl WordsGame {
+    fn create_with_rng(rng: StdRng) -> WordsGame {
+        crate::utils::set_panic_hook();
+        WordsGame {
+            db: Database::new(),
+            results: GameResults::new(),
+            exercise: None,
+            exercise_answered: false,
+            hints_used: 0,
+            session: Session::new(SessionConfig::default()).unwrap(),
+            rng,
+            daily_challenge: false,
+            ui_language: UiLanguage::default(),
         }
     }
 
-    pub fn get_correct_spelling(&self) -> JsValue {
-        match &self.exercise {
-            None => JsValue::UNDEFINED,
-            Some(ex) => JsValue::from_str(
-                &ex.get_correct_spelling()
-            ),
+    fn verdict(&self, correct: bool, pairs: Vec<bool>) -> Verdict {
+        let (message, correct_answer) = match &self.exercise {
+            None => (String::new(), None),
+            Some(ex) => {
+                let message = match correct {
+                    true => ex.get_correct_message(),
+                    false => ex.incorrect_message.clone(),
+                };
+                let spelling = ex.get_correct_spelling();
+                (
+                    message.format(self.ui_language),
+                    (!spelling.is_empty()).then(|| spelling.to_owned()),
+                )
+            }
+        };
+        Verdict {
+            correct,
+            message,
+            correct_answer,
+            pairs,
         }
     }
 
-    pub fn get_correct_message(&self) -> JsValue {
-        match &self.exercise {
-            None => JsValue::UNDEFINED,
-            Some(ex) => JsValue::from_str(
-                &ex.get_correct_message()
-            ),
+    fn record_result(&mut self, correct: bool) {
+        if let Some(ex) = &self.exercise {
+            let word = ex.word.clone();
+            self.record_results(vec![(word, correct)]);
+        }
+    }
+
+    /// Only the first answer to an exercise counts towards the results,
+    /// exercises with several words have a result for each of them
+    fn record_results(&mut self, answers: Vec<(String, bool)>) {
+        if self.exercise_answered {
+            return;
+        }
+        if let Some(ex) = &self.exercise {
+            let key = ex.key();
+            for (word, correct) in answers {
+                if !self.daily_challenge {
+                    self.results
+                        .add_result(&word, &key, correct, self.hints_used);
+                }
+                self.session.add_result(&word, &key, correct);
+            }
+            self.exercise_answered = true;
         }
     }
 }
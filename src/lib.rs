@@ -4,15 +4,923 @@ extern crate web_sys;
 mod utils;
 mod counter_state;
 mod fetch_words;
-mod words;
+pub mod words;
 mod exercise;
-
-use wasm_bindgen::prelude::*;
+mod session;
+mod statistics;
+mod messages;
 
 #[cfg(test)]
 mod tests {
+    use crate::exercise::*;
+    use crate::session::*;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::collections::HashMap;
+
+    /// Row of the sheet with the cells every word has, tests add the other columns to it
+    fn sheet_row(word: &str, pos: &str, translation: &str, group: &str) -> HashMap<usize, String> {
+        use crate::words::{GROUP_IDX, POS_IDX, TRANSLATION_IDX, WORD_IDX};
+        HashMap::from([
+            (WORD_IDX, word.to_owned()),
+            (POS_IDX, pos.to_owned()),
+            (TRANSLATION_IDX, translation.to_owned()),
+            (GROUP_IDX, group.to_owned()),
+        ])
+    }
+
     #[test]
     fn it_works() {
         assert_eq!(2 + 2, 4);
     }
+
+    #[test]
+    fn results_per_exercise_type() {
+        let mut res = ExerciseResults::new("fahren");
+        res.add(&ExerciseKey::new(ExerciseType::SelectDe), true, 0);
+        res.add(&ExerciseKey::verb_form(VerbFormExercise::Perfect), false, 0);
+        assert_eq!(res.score(), -1);
+        assert_eq!(
+            res.exercise_score(&ExerciseKey::new(ExerciseType::SelectDe)),
+            1
+        );
+        assert_eq!(
+            res.exercise_score(&ExerciseKey::verb_form(VerbFormExercise::Perfect)),
+            -2
+        );
+        assert_eq!(
+            res.exercise_score(&ExerciseKey::verb_form(VerbFormExercise::Praeteritum)),
+            0
+        );
+    }
+
+    #[test]
+    fn leitner_box_promotion() {
+        let key = ExerciseKey::new(ExerciseType::SelectNative);
+        let mut res = ExerciseResults::new("fahren");
+        assert_eq!(res.leitner_box(), 1);
+        res.add(&key, true, 0);
+        res.add(&key, true, 0);
+        assert_eq!(res.leitner_box(), 3);
+        for _ in 0..LEITNER_BOXES {
+            res.add(&key, true, 0);
+        }
+        assert_eq!(res.leitner_box(), LEITNER_BOXES);
+        res.add(&key, false, 0);
+        assert_eq!(res.leitner_box(), 1);
+    }
+
+    #[test]
+    fn legacy_results_keep_practiced_words() {
+        let mut results = GameResults::new();
+        results
+            .load_json(
+                r#"{"results": [
+                    {"word": "fahren", "correct": 2, "wrong": 1},
+                    {"word": "gehen", "correct": 4, "wrong": 0, "leitner_box": 5},
+                    {"word": "laufen", "correct": 0, "wrong": 0}
+                ]}"#,
+            )
+            .unwrap();
+        let stage = |word| {
+            results
+                .results()
+                .iter()
+                .find(|r| r.word() == word)
+                .unwrap()
+                .stage()
+        };
+        assert_eq!(stage("fahren"), WordStage::Learning);
+        assert_eq!(stage("gehen"), WordStage::Known);
+        assert_eq!(stage("laufen"), WordStage::New);
+    }
+
+    #[test]
+    fn hints_reduce_credit() {
+        let key = ExerciseKey::new(ExerciseType::TranslateNativeDe);
+        let mut res = ExerciseResults::new("fahren");
+        res.add(&key, true, 2);
+        assert_eq!(res.score(), -1);
+        assert_eq!(res.exercise_score(&key), -1);
+        assert_eq!(res.leitner_box(), 1);
+
+        // Any number of hints scores above a wrong answer and moves the word one box back
+        let mut hinted = ExerciseResults::new("gehen");
+        let mut wrong = ExerciseResults::new("laufen");
+        for res in [&mut hinted, &mut wrong] {
+            res.add(&key, true, 0);
+            res.add(&key, true, 0);
+        }
+        hinted.add(&key, true, 3);
+        wrong.add(&key, false, 0);
+        assert!(hinted.score() > wrong.score());
+        assert_eq!(hinted.leitner_box(), 2);
+        assert_eq!(wrong.leitner_box(), 1);
+    }
+
+    #[test]
+    fn session_interleave_plan() {
+        let config: SessionConfig = serde_json::from_str(
+            r#"{
+                "exercises": [
+                    {"ex_type": "SelectDe", "count": 2},
+                    {"ex_type": "SelectNative", "count": 1},
+                    {"ex_type": "TranslateNativeDe", "enabled": false}
+                ],
+                "order": "Interleave",
+                "max_exercises": 4
+            }"#,
+        )
+        .unwrap();
+        let mut session = Session::new(config).unwrap();
+        let mut rng = StdRng::seed_from_u64(0);
+        let types: Vec<_> = std::iter::from_fn(|| {
+            let ex_type = session.next_type(&mut rng)?;
+            session.exercise_created();
+            Some(ex_type)
+        })
+        .collect();
+        assert_eq!(
+            types,
+            vec![
+                ExerciseType::SelectDe,
+                ExerciseType::SelectNative,
+                ExerciseType::SelectDe,
+                ExerciseType::SelectDe
+            ]
+        );
+        assert!(session.is_finished());
+    }
+
+    #[test]
+    fn daily_challenge_is_reproducible() {
+        use crate::messages::UiLanguage;
+        use crate::words::Database;
+        let db = Database::from_json(
+            r#"{"version": 1, "languages": ["ru"], "groups": ["Alltag"], "words": [
+                {"id": "n:der:Bahnhof", "pos": "n", "word": "Bahnhof", "group": 0,
+                 "translations": ["вокзал"], "article": "der",
+                 "examples": ["Der Zug steht am *Bahnhof*."]},
+                {"id": "n:die:Zeitung", "pos": "n", "word": "Zeitung", "group": 0,
+                 "translations": ["газета"], "article": "die"},
+                {"id": "n:das:Fenster", "pos": "n", "word": "Fenster", "group": 0,
+                 "translations": ["окно"], "article": "das"},
+                {"id": "n:der:Schlüssel", "pos": "n", "word": "Schlüssel", "group": 0,
+                 "translations": ["ключ"], "article": "der"},
+                {"id": "v:fahren", "pos": "v", "word": "fahren", "group": 0,
+                 "translations": ["ехать"], "praeteritum": "fuhr", "perfect": "gefahren",
+                 "perfect_verb": "ist", "present_third": "fährt"},
+                {"id": "v:schreiben", "pos": "v", "word": "schreiben", "group": 0,
+                 "translations": ["писать"], "praeteritum": "schrieb", "perfect": "geschrieben",
+                 "perfect_verb": "hat", "present_third": "schreibt"},
+                {"id": "v:lesen", "pos": "v", "word": "lesen", "group": 0,
+                 "translations": ["читать"], "praeteritum": "las", "perfect": "gelesen",
+                 "perfect_verb": "hat", "present_third": "liest"},
+                {"id": "v:gehen", "pos": "v", "word": "gehen", "group": 0,
+                 "translations": ["идти"], "praeteritum": "ging", "perfect": "gegangen",
+                 "perfect_verb": "ist", "present_third": "geht"}
+            ]}"#,
+        )
+        .unwrap();
+        // What `WordsGame::create_daily_challenge` does for a date
+        let play = |date: &str| {
+            let mut results = GameResults::new();
+            results.update_with_db(&db);
+            results.set_clock(0.0, date);
+            let mut rng = StdRng::seed_from_u64(crate::utils::hash_str(date));
+            let mut session = Session::new(SessionConfig::daily_challenge()).unwrap();
+            let mut exercises = vec![];
+            while let Some(ex_type) = session.next_type(&mut rng) {
+                if let Ok(ex) = create_exercise_with_type(&db, &mut results, &ex_type, &mut rng) {
+                    exercises.push((
+                        ex.word.clone(),
+                        ex.task.format(UiLanguage::En),
+                        ex.get_answers().cloned(),
+                    ));
+                }
+                session.exercise_created();
+            }
+            exercises
+        };
+        let exercises = play("2026-10-18");
+        assert!(!exercises.is_empty());
+        assert_eq!(exercises, play("2026-10-18"));
+        assert_ne!(exercises, play("2026-10-19"));
+
+        let plan = |seed| {
+            let mut session = Session::new(SessionConfig::daily_challenge()).unwrap();
+            let mut rng = StdRng::seed_from_u64(seed);
+            std::iter::from_fn(|| {
+                let ex_type = session.next_type(&mut rng)?;
+                session.exercise_created();
+                Some(ex_type)
+            })
+            .collect::<Vec<_>>()
+        };
+        let seed = crate::utils::hash_str("2026-10-18");
+        assert_eq!(plan(seed), plan(seed));
+        let max = SessionConfig::daily_challenge().max_exercises;
+        assert_eq!(Some(plan(1).len()), max);
+    }
+
+    #[test]
+    fn failed_exercises_fall_back_without_side_effects() {
+        use crate::words::Database;
+        // Too few words in the group to match pairs and no verbs
+        let db = Database::from_json(
+            r#"{"version": 1, "languages": ["ru"], "groups": ["Alltag"], "words": [
+                {"id": "n:der:Bahnhof", "pos": "n", "word": "Bahnhof", "group": 0,
+                 "translations": ["вокзал"], "article": "der"},
+                {"id": "n:die:Zeitung", "pos": "n", "word": "Zeitung", "group": 0,
+                 "translations": ["газета"], "article": "die"},
+                {"id": "n:das:Fenster", "pos": "n", "word": "Fenster", "group": 0,
+                 "translations": ["окно"], "article": "das"}
+            ]}"#,
+        )
+        .unwrap();
+        let mut results = GameResults::new();
+        results.update_with_db(&db);
+        results.set_new_words_limit(Some(NewWordsLimit::PerSession(1)));
+        let mut rng = StdRng::seed_from_u64(1);
+        let learning = |results: &GameResults| {
+            results
+                .results()
+                .iter()
+                .filter(|r| r.stage() == WordStage::Learning)
+                .count()
+        };
+
+        let res = create_exercise_with_fallback(
+            &db,
+            &mut results,
+            ExerciseType::MatchPairs,
+            &[],
+            &mut rng,
+        );
+        assert!(matches!(res, Err(ExerciseError::NotEnoughAnswerOptions(_))));
+        assert_eq!(learning(&results), 0);
+
+        // The only new word of the session is introduced by the exercise that is created
+        let ex = create_exercise_with_fallback(
+            &db,
+            &mut results,
+            ExerciseType::VerbFormRandom,
+            &[ExerciseType::MatchPairs, ExerciseType::TranslateNativeDe],
+            &mut rng,
+        )
+        .unwrap();
+        assert_eq!(ex.ex_type, ExerciseType::TranslateNativeDe);
+        assert_eq!(learning(&results), 1);
+    }
+
+    #[test]
+    fn confusions_make_confusables_exercise() {
+        use crate::words::Database;
+        let db = Database::from_json(
+            r#"{"version": 1, "languages": ["ru"], "groups": ["Alltag"], "words": [
+                {"id": "n:der:Bahnhof", "pos": "n", "word": "Bahnhof", "group": 0,
+                 "translations": ["вокзал"], "article": "der"},
+                {"id": "n:die:Zeitung", "pos": "n", "word": "Zeitung", "group": 0,
+                 "translations": ["газета"], "article": "die"},
+                {"id": "n:das:Fenster", "pos": "n", "word": "Fenster", "group": 0,
+                 "translations": ["окно"], "article": "das"}
+            ]}"#,
+        )
+        .unwrap();
+        let mut results = GameResults::new();
+        results.update_with_db(&db);
+        let mut rng = StdRng::seed_from_u64(1);
+        let confusables = ExerciseType::Confusables;
+        let res = create_exercise_with_type(&db, &mut results, &confusables, &mut rng);
+        assert!(matches!(res, Err(ExerciseError::NoWords(_))));
+        assert!(!SessionConfig::default()
+            .exercises
+            .iter()
+            .any(|plan| plan.ex_type == confusables && plan.enabled));
+
+        results.add_confusion("n:der:Bahnhof", "n:das:Fenster");
+        results.add_confusion("n:das:Fenster", "n:der:Bahnhof");
+        results.add_confusion("n:der:Bahnhof", "n:das:Fenster");
+        assert_eq!(results.confusions().len(), 2);
+        assert_eq!(results.confusions()[0].count(), 2);
+        assert_eq!(results.confusion_count("n:das:Fenster", "n:der:Bahnhof"), 3);
+
+        let ex = create_exercise_with_type(&db, &mut results, &confusables, &mut rng).unwrap();
+        let other = match ex.word.as_str() {
+            "n:der:Bahnhof" => "n:das:Fenster",
+            _ => "n:der:Bahnhof",
+        };
+        let mut answers = ex.get_answers().unwrap().clone();
+        answers.sort();
+        assert_eq!(answers, vec!["das Fenster", "der Bahnhof"]);
+        let wrong = (0..2).find(|&i| !ex.check_answer(i)).unwrap();
+        assert_eq!(ex.get_answer_word(wrong), Some(other));
+    }
+
+    #[test]
+    fn statistics_of_results_and_session() {
+        use crate::statistics::collect_statistics;
+        use crate::words::Database;
+        let db = Database::from_json(
+            r#"{"version": 1, "languages": ["ru"], "groups": ["Alltag", "Reisen"], "words": [
+                {"id": "n:die:Zeitung", "pos": "n", "word": "Zeitung", "group": 0,
+                 "translations": ["газета"], "article": "die"},
+                {"id": "n:der:Bahnhof", "pos": "n", "word": "Bahnhof", "group": 1,
+                 "translations": ["вокзал"], "article": "der"},
+                {"id": "v:fahren", "pos": "v", "word": "fahren", "group": 1,
+                 "translations": ["ехать"], "praeteritum": "fuhr", "perfect": "gefahren",
+                 "perfect_verb": "ist", "present_third": "fährt"}
+            ]}"#,
+        )
+        .unwrap();
+        let mut results = GameResults::new();
+        results.update_with_db(&db);
+        let mut session = Session::new(SessionConfig::default()).unwrap();
+        let select = ExerciseKey::new(ExerciseType::SelectDe);
+        let translate = ExerciseKey::new(ExerciseType::TranslateNativeDe);
+
+        results.set_clock(0.0, "2024-05-01");
+        for _ in 0..5 {
+            results.add_result("n:die:Zeitung", &select, true, 0);
+        }
+        results.add_result("v:fahren", &translate, false, 0);
+        // Only the answers of the second day are in the session
+        results.set_clock(86400.0, "2024-05-02");
+        for (key, correct) in [(&select, false), (&translate, true)] {
+            results.add_result("n:der:Bahnhof", key, correct, 0);
+            session.add_result("n:der:Bahnhof", key, correct);
+        }
+
+        let stats = serde_json::to_value(collect_statistics(&db, &results, &session)).unwrap();
+        let accuracy = |period: &str, list: &str, field: &str, value: &str| {
+            let entry = stats[period][list]
+                .as_array()
+                .unwrap()
+                .iter()
+                .find(|e| e.pointer(field).unwrap() == value)
+                .unwrap();
+            let count = |name: &str| entry["accuracy"][name].as_u64().unwrap();
+            (count("correct"), count("wrong"))
+        };
+        assert_eq!(accuracy("all_time", "by_exercise", "/key/ex_type", "SelectDe"), (5, 1));
+        assert_eq!(
+            accuracy("all_time", "by_exercise", "/key/ex_type", "TranslateNativeDe"),
+            (1, 1)
+        );
+        assert_eq!(accuracy("all_time", "by_pos", "/pos", "Noun"), (6, 1));
+        assert_eq!(accuracy("all_time", "by_pos", "/pos", "Verb"), (0, 1));
+        assert_eq!(accuracy("all_time", "by_group", "/group", "Alltag"), (5, 0));
+        assert_eq!(accuracy("all_time", "by_group", "/group", "Reisen"), (1, 2));
+        assert_eq!(accuracy("session", "by_exercise", "/key/ex_type", "SelectDe"), (0, 1));
+        assert_eq!(accuracy("session", "by_group", "/group", "Reisen"), (1, 1));
+        assert_eq!(stats["session"]["by_group"].as_array().unwrap().len(), 1);
+
+        assert_eq!(stats["hardest_words"][0]["id"], "v:fahren");
+        assert_eq!(stats["hardest_words"][1]["id"], "n:der:Bahnhof");
+        assert_eq!(stats["mastered_words"], 1);
+        assert_eq!(
+            stats["history"],
+            serde_json::json!([
+                {"day": "2024-05-01", "correct": 5, "wrong": 1},
+                {"day": "2024-05-02", "correct": 1, "wrong": 1}
+            ])
+        );
+
+        let summary = serde_json::to_value(session.summary()).unwrap();
+        assert_eq!(summary["exercises"], 2);
+        assert_eq!(summary["correct"], 1);
+        assert_eq!(summary["by_type"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn cooldown_in_exercises_and_seconds() {
+        use crate::words::Database;
+        let db = Database::from_json(
+            r#"{"version": 1, "languages": ["ru"], "groups": ["Alltag"], "words": [
+                {"id": "n:der:Bahnhof", "pos": "n", "word": "Bahnhof", "group": 0,
+                 "translations": ["вокзал"], "article": "der"},
+                {"id": "n:die:Zeitung", "pos": "n", "word": "Zeitung", "group": 0,
+                 "translations": ["газета"], "article": "die"},
+                {"id": "n:das:Fenster", "pos": "n", "word": "Fenster", "group": 0,
+                 "translations": ["окно"], "article": "das"}
+            ]}"#,
+        )
+        .unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        // Both `select_word_to_learn` and `select_word_by_cmp` respect the cooldown
+        let types = [ExerciseType::TranslateNativeDe, ExerciseType::GuessNounArticle];
+        let mut results = GameResults::new();
+        results.update_with_db(&db);
+
+        results.set_cooldown(Cooldown::Exercises(2));
+        let words: Vec<String> = (0..6)
+            .map(|i| {
+                create_exercise_with_type(&db, &mut results, &types[i % 2], &mut rng)
+                    .unwrap()
+                    .word
+            })
+            .collect();
+        for i in 2..words.len() {
+            assert_ne!(words[i], words[i - 1]);
+            assert_ne!(words[i], words[i - 2]);
+        }
+        // The cooldown is ignored when all words are recent
+        results.set_cooldown(Cooldown::Exercises(5));
+        for ex_type in types.iter().cycle().take(6) {
+            assert!(create_exercise_with_type(&db, &mut results, ex_type, &mut rng).is_ok());
+        }
+
+        let mut results = GameResults::new();
+        results.update_with_db(&db);
+        results.set_cooldown(Cooldown::Seconds(60.0));
+        let mut word_at = |time: f64, ex_type: &ExerciseType| {
+            results.set_clock(time, "2024-05-01");
+            create_exercise_with_type(&db, &mut results, ex_type, &mut rng)
+                .unwrap()
+                .word
+        };
+        let first = word_at(0.0, &types[0]);
+        let second = word_at(10.0, &types[1]);
+        let third = word_at(20.0, &types[0]);
+        assert!(first != second && first != third && second != third);
+        assert_eq!(word_at(65.0, &types[1]), first);
+        assert_eq!(word_at(70.0, &types[0]), second);
+        assert_eq!(word_at(80.0, &types[1]), third);
+        // Every word was selected less than a minute ago
+        word_at(90.0, &types[0]);
+    }
+
+    #[test]
+    fn new_words_per_session_and_day() {
+        use crate::words::Database;
+        let db = Database::from_json(
+            r#"{"version": 1, "languages": ["ru"], "groups": ["Alltag"], "words": [
+                {"id": "n:der:Bahnhof", "pos": "n", "word": "Bahnhof", "group": 0,
+                 "translations": ["вокзал"], "article": "der"},
+                {"id": "n:die:Zeitung", "pos": "n", "word": "Zeitung", "group": 0,
+                 "translations": ["газета"], "article": "die"},
+                {"id": "n:das:Fenster", "pos": "n", "word": "Fenster", "group": 0,
+                 "translations": ["окно"], "article": "das"}
+            ]}"#,
+        )
+        .unwrap();
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut introduced = |results: &mut GameResults| {
+            create_exercise_with_type(&db, results, &ExerciseType::TranslateNativeDe, &mut rng)
+                .unwrap();
+            results
+                .results()
+                .iter()
+                .filter(|r| r.stage() != WordStage::New)
+                .count()
+        };
+
+        let mut results = GameResults::new();
+        results.update_with_db(&db);
+        results.set_new_words_limit(Some(NewWordsLimit::PerSession(1)));
+        assert_eq!(introduced(&mut results), 1);
+        assert_eq!(introduced(&mut results), 1);
+        results.start_session();
+        assert_eq!(introduced(&mut results), 2);
+        assert_eq!(introduced(&mut results), 2);
+
+        let mut results = GameResults::new();
+        results.update_with_db(&db);
+        results.set_new_words_limit(Some(NewWordsLimit::PerDay(1)));
+        results.set_clock(0.0, "2024-05-01");
+        assert_eq!(introduced(&mut results), 1);
+        results.start_session();
+        assert_eq!(introduced(&mut results), 1);
+        results.set_clock(86400.0, "2024-05-02");
+        assert_eq!(introduced(&mut results), 2);
+        assert_eq!(introduced(&mut results), 2);
+    }
+
+    #[test]
+    fn word_status_in_every_exercise() {
+        use crate::words::Database;
+        let db = Database::from_json(
+            r#"{"version": 1, "languages": ["ru"], "groups": ["Alltag"], "words": [
+                {"id": "n:der:Bahnhof", "pos": "n", "word": "Bahnhof", "group": 0,
+                 "translations": ["вокзал"], "article": "der"},
+                {"id": "n:die:Zeitung", "pos": "n", "word": "Zeitung", "group": 0,
+                 "translations": ["газета"], "article": "die"},
+                {"id": "n:das:Fenster", "pos": "n", "word": "Fenster", "group": 0,
+                 "translations": ["окно"], "article": "das"},
+                {"id": "n:der:Schlüssel", "pos": "n", "word": "Schlüssel", "group": 0,
+                 "translations": ["ключ"], "article": "der"},
+                {"id": "n:der:Tisch", "pos": "n", "word": "Tisch", "group": 0,
+                 "translations": ["стол"], "article": "der"},
+                {"id": "n:der:Stuhl", "pos": "n", "word": "Stuhl", "group": 0,
+                 "translations": ["стул"], "article": "der"}
+            ]}"#,
+        )
+        .unwrap();
+        let mut results = GameResults::new();
+        results.update_with_db(&db);
+        let (mastered, suspended) = ("n:die:Zeitung", "n:das:Fenster");
+        assert!(results.set_word_status(mastered, WordStatus::Mastered));
+        assert!(results.set_word_status(suspended, WordStatus::Suspended));
+        assert!(!results.set_word_status("n:der:Hund", WordStatus::Mastered));
+        assert_eq!(results.get_words_with_status(WordStatus::Mastered), vec![mastered]);
+        // Only the last confusion is between active words
+        results.add_confusion("n:der:Bahnhof", mastered);
+        results.add_confusion("n:der:Bahnhof", suspended);
+        results.add_confusion(mastered, "n:der:Bahnhof");
+        results.add_confusion("n:der:Tisch", "n:der:Stuhl");
+
+        let mut rng = StdRng::seed_from_u64(1);
+        let inactive = [mastered, suspended];
+        for _ in 0..10 {
+            let mut create = |ex_type| {
+                create_exercise_with_type(&db, &mut results, &ex_type, &mut rng).unwrap()
+            };
+            for ex_type in [
+                ExerciseType::TranslateNativeDe,
+                ExerciseType::GuessNounArticle,
+            ] {
+                assert!(!inactive.contains(&create(ex_type).word.as_str()));
+            }
+            // Mastered words are still answer options, suspended ones are not
+            let ex = create(ExerciseType::SelectDe);
+            assert!(!inactive.contains(&ex.word.as_str()));
+            assert!((0..4).all(|i| ex.get_answer_word(i) != Some(suspended)));
+            let ex = create(ExerciseType::MatchPairs);
+            match &ex.data {
+                ExerciseData::Pairs(data) => {
+                    assert!(data.word_ids.iter().all(|w| !inactive.contains(&w.as_str())))
+                }
+                _ => panic!("Not a matching exercise"),
+            }
+            assert_eq!(create(ExerciseType::Confusables).word, "n:der:Tisch");
+        }
+    }
+
+    #[test]
+    fn matching_pairs_graded_per_word() {
+        use crate::words::Database;
+        let db = Database::from_json(
+            r#"{"version": 1, "languages": ["ru"], "groups": ["Alltag"], "words": [
+                {"id": "n:der:Bahnhof", "pos": "n", "word": "Bahnhof", "group": 0,
+                 "translations": ["вокзал"], "article": "der"},
+                {"id": "n:die:Zeitung", "pos": "n", "word": "Zeitung", "group": 0,
+                 "translations": ["газета"], "article": "die"},
+                {"id": "n:das:Fenster", "pos": "n", "word": "Fenster", "group": 0,
+                 "translations": ["окно"], "article": "das"},
+                {"id": "n:der:Schlüssel", "pos": "n", "word": "Schlüssel", "group": 0,
+                 "translations": ["ключ"], "article": "der"}
+            ]}"#,
+        )
+        .unwrap();
+        let mut results = GameResults::new();
+        results.update_with_db(&db);
+        let mut rng = StdRng::seed_from_u64(1);
+        let ex = create_exercise_with_type(&db, &mut results, &ExerciseType::MatchPairs, &mut rng)
+            .unwrap();
+        let data = match &ex.data {
+            ExerciseData::Pairs(data) => data,
+            _ => panic!("Not a matching exercise"),
+        };
+        let mut order = data.correct.clone();
+        order.sort();
+        assert_eq!(order, vec![0, 1, 2, 3]);
+        for (word, &correct) in data.word_ids.iter().zip(data.correct.iter()) {
+            assert_eq!(db.words[word].translation(), data.translations[correct]);
+        }
+
+        // The translations of the first two words are swapped
+        let mut answer = data.correct.clone();
+        answer.swap(0, 1);
+        let graded = ex.check_pairs(&answer);
+        let ids = &data.word_ids;
+        assert_eq!(graded[0], (ids[0].as_str(), Some(ids[1].as_str()), false));
+        assert_eq!(graded[1], (ids[1].as_str(), Some(ids[0].as_str()), false));
+        assert!(graded[2].2 && graded[3].2);
+
+        results.add_pair_results(&ex.key(), &graded, 0);
+        let counts = |word: &str| {
+            let res = results.results().iter().find(|r| r.word() == word).unwrap();
+            (res.correct(), res.wrong())
+        };
+        assert_eq!(counts(&ids[0]), (0, 1));
+        assert_eq!(counts(&ids[1]), (0, 1));
+        assert_eq!(counts(&ids[2]), (1, 0));
+        assert_eq!(counts(&ids[3]), (1, 0));
+        let confusions: Vec<_> = results
+            .confusions()
+            .iter()
+            .map(|c| (c.word(), c.confused_with()))
+            .collect();
+        assert_eq!(
+            confusions,
+            vec![(ids[0].as_str(), ids[1].as_str()), (ids[1].as_str(), ids[0].as_str())]
+        );
+    }
+
+    #[test]
+    fn anagram_of_long_words() {
+        use crate::messages::{MessageId, Param};
+        use crate::words::Database;
+        let db = Database::from_json(
+            r#"{"version": 1, "languages": ["ru"], "groups": ["Alltag"], "words": [
+                {"id": "n:der:Schlüssel", "pos": "n", "word": "Schlüssel", "group": 0,
+                 "translations": ["ключ"], "article": "der"},
+                {"id": "v:schreiben", "pos": "v", "word": "schreiben", "group": 0,
+                 "translations": ["писать"], "praeteritum": "schrieb", "perfect": "geschrieben",
+                 "perfect_verb": "hat", "present_third": "schreibt"},
+                {"id": "n:der:Tisch", "pos": "n", "word": "Tisch", "group": 0,
+                 "translations": ["стол"], "article": "der"},
+                {"id": "v:Rad fahren", "pos": "v", "word": "Rad fahren", "group": 0,
+                 "translations": ["кататься на велосипеде"], "praeteritum": "fuhr Rad",
+                 "perfect": "Rad gefahren", "perfect_verb": "ist", "present_third": "fährt Rad"},
+                {"id": "adj:wunderbar", "pos": "adj", "word": "wunderbar", "group": 0,
+                 "translations": ["чудесный"]}
+            ]}"#,
+        )
+        .unwrap();
+        let mut results = GameResults::new();
+        results.update_with_db(&db);
+        let mut rng = StdRng::seed_from_u64(1);
+        let text = |param: &Param| match param {
+            Param::Text(text) => text.clone(),
+            _ => panic!("Not a text parameter"),
+        };
+        let sorted = |s: &str| {
+            let mut chars: Vec<char> = s.chars().filter(|c| *c != ' ').collect();
+            chars.sort();
+            chars
+        };
+        for _ in 0..10 {
+            let ex = create_exercise_with_type(&db, &mut results, &ExerciseType::Anagram, &mut rng)
+                .unwrap();
+            let letters = text(&ex.task.params[0]);
+            match ex.word.as_str() {
+                "n:der:Schlüssel" => {
+                    assert_eq!(ex.task.id, MessageId::TaskAnagramArticle);
+                    assert_eq!(text(&ex.task.params[2]), "der");
+                    assert_eq!(sorted(&letters), sorted("schlüssel"));
+                    assert!(ex.check_spelling("Schlüssel"));
+                }
+                "v:schreiben" => {
+                    assert_eq!(ex.task.id, MessageId::TaskAnagram);
+                    assert_eq!(sorted(&letters), sorted("schreiben"));
+                    assert!(ex.check_spelling("schreiben"));
+                }
+                word => panic!("Anagram of {}", word),
+            }
+        }
+    }
+
+    #[test]
+    fn cloze_of_example() {
+        use crate::words::cloze_sentence;
+        assert_eq!(
+            cloze_sentence("Er *fuhr* gestern nach Berlin."),
+            Some(("Er ___ gestern nach Berlin.".to_owned(), "fuhr".to_owned()))
+        );
+        assert_eq!(cloze_sentence("Er fuhr nach Berlin."), None);
+    }
+
+    #[test]
+    fn message_in_ui_language() {
+        use crate::messages::*;
+        use crate::words::PartOfSpeech;
+        let message = Message::new(MessageId::TaskSelectNative)
+            .with_language("en")
+            .with("fahren")
+            .with_pos(PartOfSpeech::Verb);
+        assert_eq!(
+            message.format(UiLanguage::De),
+            "Wähle die Übersetzung (Englisch): fahren (Verb)"
+        );
+        assert_eq!(
+            message.format(UiLanguage::Ru),
+            "Выберите перевод (английский): fahren (глагол)"
+        );
+        assert_eq!(
+            Message::new(MessageId::IncorrectTranslation)
+                .with("ехать")
+                .format(UiLanguage::En),
+            "Incorrect! The translation is ехать"
+        );
+        assert_eq!(
+            Message::new(MessageId::IncorrectArticle)
+                .with("{1}")
+                .with("Bahnhof")
+                .format(UiLanguage::En),
+            "Incorrect! The article is {1} - Bahnhof"
+        );
+    }
+
+    #[test]
+    fn search_words_ignoring_umlauts() {
+        use crate::words::*;
+        let mut db = Database::new();
+        for (word, pos, translation) in [
+            ("grüßen", "v", "приветствовать"),
+            ("Gruß", "n", "привет"),
+            ("übergrüßen", "v", "-"),
+            ("Bär", "n", "медведь"),
+        ] {
+            let mut map = sheet_row(word, pos, translation, "Alltag");
+            map.insert(ARTICLE_IDX, "der".to_owned());
+            let word = Word::new(&mut map, &mut db).unwrap();
+            db.add_word(word);
+        }
+
+        let query = WordQuery {
+            text: "gruess".to_owned(),
+            ..Default::default()
+        };
+        let found: Vec<_> = db.search(&query).iter().map(|w| w.get_word()).collect();
+        assert_eq!(found, vec!["grüßen", "übergrüßen"]);
+
+        // Umlauts typed as the plain vowel
+        let search = |text: &str| -> Vec<&str> {
+            let query = WordQuery {
+                text: text.to_owned(),
+                ..Default::default()
+            };
+            db.search(&query).iter().map(|w| w.get_word()).collect()
+        };
+        assert_eq!(search("grussen"), vec!["grüßen", "übergrüßen"]);
+        assert_eq!(search("Bar"), vec!["Bär"]);
+        assert_eq!(search("gruss"), vec!["grüßen", "Gruß", "übergrüßen"]);
+
+        let query = WordQuery {
+            text: "прив".to_owned(),
+            mode: MatchMode::Prefix,
+            pos: Some(PartOfSpeech::Noun),
+            ..Default::default()
+        };
+        let found: Vec<_> = db.search(&query).iter().map(|w| w.get_word()).collect();
+        assert_eq!(found, vec!["Gruß"]);
+    }
+
+    #[test]
+    fn homographs_have_own_ids() {
+        use crate::words::*;
+        let mut db = Database::new();
+        for (article, translation) in [("die", "лестница"), ("der", "руководитель"), ("die", "-")] {
+            let mut map = sheet_row("Leiter", "n", translation, "Alltag");
+            map.insert(ARTICLE_IDX, article.to_owned());
+            let noun = Word::new(&mut map, &mut db).unwrap();
+            db.add_word(noun);
+        }
+        assert_eq!(db.order, vec!["n:die:Leiter", "n:der:Leiter"]);
+        assert_eq!(db.words["n:die:Leiter"].translation(), "лестница");
+        assert_eq!(db.warnings.len(), 1);
+
+        let mut results = GameResults::new();
+        results
+            .load_json(r#"{"results": [{"word": "Leiter", "correct": 1, "wrong": 0}]}"#)
+            .unwrap();
+        let warnings = results.update_with_db(&db);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("n:der:Leiter"));
+    }
+
+    #[test]
+    fn vocabulary_json_round_trip() {
+        use crate::words::*;
+        let mut db = Database::new();
+        let mut map = sheet_row("fahren", "v", "ехать", "Reisen");
+        map.extend([
+            (PERFECT_IDX, "gefahren".to_owned()),
+            (PRAETERITUM_IDX, "fuhr".to_owned()),
+            (HELP_IDX, "f-".to_owned()),
+            (PERFECT_VERB_IDX, "ist".to_owned()),
+            (PRESENT_THIRD_IDX, "fährt".to_owned()),
+            (EXAMPLES_IDX, "Wir *fahren* nach Berlin.".to_owned()),
+        ]);
+        let verb = Word::new(&mut map, &mut db).unwrap();
+        db.add_word(verb);
+
+        let json = db.to_json();
+        let loaded = Database::from_json(&json).unwrap();
+        assert_eq!(loaded.to_json(), json);
+        let verb = &loaded.words["v:fahren"];
+        assert_eq!(verb.get_verb_perfect_full().as_deref(), Some("ist gefahren"));
+        assert_eq!(loaded.groups[verb.get_group_id()], "Reisen");
+        assert!(Database::from_json(&json.replace("\"v\"", "\"x\"")).is_err());
+    }
+
+    #[test]
+    fn phrases_and_unknown_pos() {
+        use crate::words::*;
+        use std::collections::HashSet;
+        let mut db = Database::new();
+        let row = |word: &str, pos: &str| sheet_row(word, pos, "-", "Alltag");
+        let phrase = Word::new(&mut row("Wie geht's?", "phrase"), &mut db).unwrap();
+        assert_eq!(phrase.get_pos(), PartOfSpeech::Phrase);
+        assert!(Word::new(&mut row("obwohl", "conj"), &mut db).is_ok());
+        assert!(Word::new(&mut row("Etwas", "xyz"), &mut db).is_err());
+
+        assert!(check_spelling_phrase("wie  gehts", phrase.get_word()));
+        assert!(check_spelling_phrase("Zum Schluss.", "zum Schluss"));
+        assert!(!check_spelling_phrase("zum", "zum Schluss"));
+
+        // Only phrases are checked word by word, other words keep their hyphens
+        let mut map = sheet_row("E-Mail", "n", "-", "Post");
+        map.insert(ARTICLE_IDX, "die".to_owned());
+        let noun = Word::new(&mut map, &mut db).unwrap();
+        db.add_word(noun);
+        db.add_word(phrase);
+        let mut results = GameResults::new();
+        results.update_with_db(&db);
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut translate = |group: &str| {
+            let group = db.groups.iter().position(|g| g == group).unwrap();
+            results.set_groups(Some(HashSet::from([group])));
+            create_exercise_with_type(&db, &mut results, &ExerciseType::TranslateNativeDe, &mut rng)
+                .unwrap()
+        };
+        let ex = translate("Post");
+        assert!(ex.check_spelling("die E-Mail"));
+        assert!(!ex.check_spelling("die E Mail"));
+        assert!(!ex.check_spelling("die EMail"));
+        let ex = translate("Alltag");
+        assert!(ex.check_spelling("wie  gehts"));
+    }
+
+    #[test]
+    fn warnings_name_sheet_rows() {
+        use crate::words::Database;
+        use calamine::{DataType, Range};
+        // The first two rows of the sheet are empty, the header is on the fourth one
+        let mut range = Range::new((2, 0), (4, 3));
+        for (col, cell) in ["Word", "PoS", "Translation", "Group"].into_iter().enumerate() {
+            range.set_value((3, col as u32), DataType::String(cell.to_owned()));
+        }
+        for (col, cell) in ["Etwas", "xyz", "-", "Alltag"].into_iter().enumerate() {
+            range.set_value((4, col as u32), DataType::String(cell.to_owned()));
+        }
+        let mut db = Database::new();
+        db.read_sheet(&range);
+        assert!(db.warnings[0].starts_with("Row 5:"));
+    }
+
+    #[test]
+    fn edit_distance_of_words() {
+        use crate::words::edit_distance;
+        assert_eq!(edit_distance("fahren", "fahren"), 0);
+        assert_eq!(edit_distance("fahren", "fuhren"), 1);
+        assert_eq!(edit_distance("Bär", "Bier"), 2);
+        assert_eq!(edit_distance("", "ab"), 2);
+    }
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use crate::exercise::ExerciseType;
+    use crate::fetch_words::WordsGame;
+    use crate::session::{ExercisePlan, SessionConfig};
+    use js_sys::Reflect;
+    use tsify::Tsify;
+    use wasm_bindgen::{JsCast, JsValue};
+    use wasm_bindgen_test::*;
+
+    fn field(value: &JsValue, name: &str) -> JsValue {
+        Reflect::get(value, &JsValue::from_str(name)).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn statistics_are_plain_objects() {
+        let mut game = WordsGame::create_with_seed(1);
+        game.load_words_json(
+            r#"{"version": 1, "languages": ["ru"], "groups": ["Alltag"], "words": [
+                {"id": "n:der:Bahnhof", "pos": "n", "word": "Bahnhof", "group": 0,
+                 "translations": ["вокзал"], "article": "der"},
+                {"id": "n:die:Zeitung", "pos": "n", "word": "Zeitung", "group": 0,
+                 "translations": ["газета"], "article": "die"},
+                {"id": "n:das:Fenster", "pos": "n", "word": "Fenster", "group": 0,
+                 "translations": ["окно"], "article": "das"},
+                {"id": "n:der:Schlüssel", "pos": "n", "word": "Schlüssel", "group": 0,
+                 "translations": ["ключ"], "article": "der"}
+            ]}"#,
+        )
+        .unwrap();
+        let config = SessionConfig {
+            exercises: vec![ExercisePlan {
+                ex_type: ExerciseType::SelectDe,
+                enabled: true,
+                count: None,
+                ratio: None,
+            }],
+            ..SessionConfig::default()
+        };
+        game.set_session_config(config).unwrap();
+        game.create_exercise().unwrap().unwrap();
+        game.check_answer(usize::MAX);
+
+        // The same conversion as returning the statistics to JavaScript
+        let stats: JsValue = game.get_statistics().into_js().unwrap().into();
+        let by_exercise = field(&field(&stats, "session"), "by_exercise");
+        let first = js_sys::Array::from(&by_exercise).get(0);
+        assert!(!first.is_instance_of::<js_sys::Map>());
+        assert_eq!(field(&field(&first, "key"), "ex_type"), "SelectDe");
+        let accuracy = field(&first, "accuracy");
+        assert_eq!(field(&accuracy, "correct").as_f64(), Some(0.0));
+        assert_eq!(field(&accuracy, "wrong").as_f64(), Some(1.0));
+    }
 }
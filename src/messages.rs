@@ -0,0 +1,367 @@
+use serde::{Deserialize, Serialize};
+
+use crate::words::PartOfSpeech;
+use strum_macros::{AsRefStr, EnumIter, EnumString};
+
+/// Language of the interface and the exercise texts
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Default,
+    EnumIter,
+    EnumString,
+    AsRefStr,
+    Serialize,
+    Deserialize,
+)]
+#[strum(serialize_all = "lowercase")]
+#[serde(rename_all = "lowercase")]
+pub enum UiLanguage {
+    #[default]
+    En,
+    De,
+    Ru,
+}
+
+/// Id of a message in the catalogs, the page refers to it in snake case, e.g. "session_result"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, EnumString, AsRefStr)]
+#[strum(serialize_all = "snake_case")]
+pub enum MessageId {
+    TaskSelectDe,
+    TaskSelectNative,
+    TaskTranslateDe,
+    TaskTranslateDeHint,
+    TaskArticle,
+    TaskVerbPresentThird,
+    TaskVerbPraeteritum,
+    TaskVerbPerfect,
+    TaskConfusables,
+    TaskAnagram,
+    TaskAnagramArticle,
+    TaskCloze,
+    TaskMatchPairs,
+    Correct,
+    CorrectDetails,
+    Incorrect,
+    IncorrectWord,
+    IncorrectTranslation,
+    IncorrectArticle,
+    IncorrectForm,
+    HintFirstLetter,
+    HintLength,
+    HintArticle,
+    SelectOneAnswer,
+    TypeAnswer,
+    SelectPairs,
+    SessionFinished,
+    SessionResult,
+    Loading,
+    WordsInVocabulary,
+    ButtonStart,
+    ButtonSubmit,
+    ButtonNext,
+    ButtonHint,
+    LanguageRu,
+    LanguageEn,
+    LanguageUk,
+    LanguageDe,
+    PosNoun,
+    PosVerb,
+    PosAdjective,
+    PosAdverb,
+    PosPreposition,
+    PosPronoun,
+    PosConjunction,
+    PosNumeral,
+    PosPhrase,
+    ErrorNoWords,
+    ErrorNotEnoughAnswerOptions,
+    ErrorNoArticle,
+    ErrorNoVerbForm,
+    ErrorNoExample,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Param {
+    Text(String),
+    /// Language code, shown as the language name
+    Language(String),
+    PartOfSpeech(PartOfSpeech),
+}
+
+/// Message with the parameters, formatted in the UI language when shown
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub id: MessageId,
+    pub params: Vec<Param>,
+}
+
+impl Message {
+    pub fn new(id: MessageId) -> Self {
+        Self { id, params: vec![] }
+    }
+
+    pub fn with(mut self, param: impl Into<String>) -> Self {
+        self.params.push(Param::Text(param.into()));
+        self
+    }
+
+    pub fn with_language(mut self, code: &str) -> Self {
+        self.params.push(Param::Language(code.to_owned()));
+        self
+    }
+
+    pub fn with_pos(mut self, pos: PartOfSpeech) -> Self {
+        self.params.push(Param::PartOfSpeech(pos));
+        self
+    }
+
+    /// Parameters as shown in the UI language
+    pub fn format_params(&self, lang: UiLanguage) -> Vec<String> {
+        self.params
+            .iter()
+            .map(|param| match param {
+                Param::Text(s) => s.clone(),
+                Param::Language(code) => language_name(lang, code),
+                Param::PartOfSpeech(pos) => template(lang, pos_message(*pos)).to_owned(),
+            })
+            .collect()
+    }
+
+    /// Template with the parameters in place of `{0}`, `{1}` etc.,
+    /// placeholders inside the parameters are kept as they are
+    pub fn format(&self, lang: UiLanguage) -> String {
+        let params = self.format_params(lang);
+        let mut text = String::new();
+        let mut rest = template(lang, self.id);
+        while let Some(start) = rest.find('{') {
+            text.push_str(&rest[..start]);
+            let param = rest[start + 1..].find('}').and_then(|len| {
+                let value = params.get(rest[start + 1..start + 1 + len].parse::<usize>().ok()?)?;
+                Some((value, len))
+            });
+            match param {
+                Some((value, len)) => {
+                    text.push_str(value);
+                    rest = &rest[start + len + 2..];
+                }
+                None => {
+                    text.push('{');
+                    rest = &rest[start + 1..];
+                }
+            }
+        }
+        text.push_str(rest);
+        text
+    }
+}
+
+fn language_name(lang: UiLanguage, code: &str) -> String {
+    let id = match code {
+        "ru" => MessageId::LanguageRu,
+        "en" => MessageId::LanguageEn,
+        "uk" => MessageId::LanguageUk,
+        "de" => MessageId::LanguageDe,
+        _ => return code.to_owned(),
+    };
+    template(lang, id).to_owned()
+}
+
+fn pos_message(pos: PartOfSpeech) -> MessageId {
+    match pos {
+        PartOfSpeech::Noun => MessageId::PosNoun,
+        PartOfSpeech::Verb => MessageId::PosVerb,
+        PartOfSpeech::Adjective => MessageId::PosAdjective,
+        PartOfSpeech::Adverb => MessageId::PosAdverb,
+        PartOfSpeech::Preposition => MessageId::PosPreposition,
+        PartOfSpeech::Pronoun => MessageId::PosPronoun,
+        PartOfSpeech::Conjunction => MessageId::PosConjunction,
+        PartOfSpeech::Numeral => MessageId::PosNumeral,
+        PartOfSpeech::Phrase => MessageId::PosPhrase,
+    }
+}
+
+fn template(lang: UiLanguage, id: MessageId) -> &'static str {
+    match lang {
+        UiLanguage::En => template_en(id),
+        UiLanguage::De => template_de(id),
+        UiLanguage::Ru => template_ru(id),
+    }
+}
+
+fn template_en(id: MessageId) -> &'static str {
+    use MessageId::*;
+    match id {
+        TaskSelectDe => "Select the German translation: {0} ({1})",
+        TaskSelectNative => "Select the translation to {0}: {1} ({2})",
+        TaskTranslateDe => "Translate to German: {0} ({1})",
+        TaskTranslateDeHint => "Translate to German: {0} ({1}). Hint: {2}",
+        TaskArticle => "Select the correct article for the noun: {0}",
+        TaskVerbPresentThird => "Add the verb in present tense: Er ... jetzt [ {0} - {1} ]",
+        TaskVerbPraeteritum => "Add the verb in Präteritum: Er ... einst [ {0} - {1} ]",
+        TaskVerbPerfect => "Add the verb in Perfekt: Er ... ... gestern [ {0} - {1} ]",
+        TaskConfusables => "Select the word for: {0} ({1})",
+        TaskAnagram => "Make a word of the letters: {0} [ {1} ]",
+        TaskAnagramArticle => "Make a word of the letters: {0} [ {1} ]. Article: {2}",
+        TaskCloze => "Fill in the gap: {0} [ {1} - {2} ]",
+        TaskMatchPairs => "Match the words with their translations",
+        Correct => "Correct!",
+        CorrectDetails => "Correct! {0}",
+        Incorrect => "Incorrect! {0}",
+        IncorrectWord => "Incorrect! The word is {0}",
+        IncorrectTranslation => "Incorrect! The translation is {0}",
+        IncorrectArticle => "Incorrect! The article is {0} - {1}",
+        IncorrectForm => "Incorrect! The form is {0}",
+        HintFirstLetter => "The word starts with {0}",
+        HintLength => "{0}",
+        HintArticle => "The article is {0}",
+        SelectOneAnswer => "Select one answer",
+        TypeAnswer => "Type in the answer. ß=ss, ö=oe etc.",
+        SelectPairs => "Select a word and then its translation",
+        SessionFinished => "Session finished",
+        SessionResult => "Correct answers: {0} of {1}",
+        Loading => "Loading...",
+        WordsInVocabulary => "Words in vocabulary: {0}",
+        ButtonStart => "Start",
+        ButtonSubmit => "Submit",
+        ButtonNext => "Next",
+        ButtonHint => "Hint",
+        LanguageRu => "Russian",
+        LanguageEn => "English",
+        LanguageUk => "Ukrainian",
+        LanguageDe => "German",
+        PosNoun => "noun",
+        PosVerb => "verb",
+        PosAdjective => "adjective",
+        PosAdverb => "adverb",
+        PosPreposition => "preposition",
+        PosPronoun => "pronoun",
+        PosConjunction => "conjunction",
+        PosNumeral => "numeral",
+        PosPhrase => "phrase",
+        ErrorNoWords => "No words for the exercise {0}",
+        ErrorNotEnoughAnswerOptions => "Cannot choose answer options for {0}",
+        ErrorNoArticle => "No article for {0}",
+        ErrorNoVerbForm => "No verb form for {0}",
+        ErrorNoExample => "No example for {0}",
+    }
+}
+
+fn template_de(id: MessageId) -> &'static str {
+    use MessageId::*;
+    match id {
+        TaskSelectDe => "Wähle die deutsche Übersetzung: {0} ({1})",
+        TaskSelectNative => "Wähle die Übersetzung ({0}): {1} ({2})",
+        TaskTranslateDe => "Übersetze ins Deutsche: {0} ({1})",
+        TaskTranslateDeHint => "Übersetze ins Deutsche: {0} ({1}). Tipp: {2}",
+        TaskArticle => "Wähle den richtigen Artikel für das Nomen: {0}",
+        TaskVerbPresentThird => "Setze das Verb im Präsens ein: Er ... jetzt [ {0} - {1} ]",
+        TaskVerbPraeteritum => "Setze das Verb im Präteritum ein: Er ... einst [ {0} - {1} ]",
+        TaskVerbPerfect => "Setze das Verb im Perfekt ein: Er ... ... gestern [ {0} - {1} ]",
+        TaskConfusables => "Wähle das Wort für: {0} ({1})",
+        TaskAnagram => "Bilde ein Wort aus den Buchstaben: {0} [ {1} ]",
+        TaskAnagramArticle => "Bilde ein Wort aus den Buchstaben: {0} [ {1} ]. Artikel: {2}",
+        TaskCloze => "Fülle die Lücke: {0} [ {1} - {2} ]",
+        TaskMatchPairs => "Ordne die Wörter ihren Übersetzungen zu",
+        Correct => "Richtig!",
+        CorrectDetails => "Richtig! {0}",
+        Incorrect => "Falsch! {0}",
+        IncorrectWord => "Falsch! Das Wort ist {0}",
+        IncorrectTranslation => "Falsch! Die Übersetzung ist {0}",
+        IncorrectArticle => "Falsch! Der Artikel ist {0} - {1}",
+        IncorrectForm => "Falsch! Die Form ist {0}",
+        HintFirstLetter => "Das Wort beginnt mit {0}",
+        HintLength => "{0}",
+        HintArticle => "Der Artikel ist {0}",
+        SelectOneAnswer => "Wähle eine Antwort",
+        TypeAnswer => "Gib die Antwort ein. ß=ss, ö=oe usw.",
+        SelectPairs => "Wähle ein Wort und dann seine Übersetzung",
+        SessionFinished => "Sitzung beendet",
+        SessionResult => "Richtige Antworten: {0} von {1}",
+        Loading => "Wird geladen...",
+        WordsInVocabulary => "Wörter im Wortschatz: {0}",
+        ButtonStart => "Start",
+        ButtonSubmit => "Prüfen",
+        ButtonNext => "Weiter",
+        ButtonHint => "Tipp",
+        LanguageRu => "Russisch",
+        LanguageEn => "Englisch",
+        LanguageUk => "Ukrainisch",
+        LanguageDe => "Deutsch",
+        PosNoun => "Nomen",
+        PosVerb => "Verb",
+        PosAdjective => "Adjektiv",
+        PosAdverb => "Adverb",
+        PosPreposition => "Präposition",
+        PosPronoun => "Pronomen",
+        PosConjunction => "Konjunktion",
+        PosNumeral => "Numerale",
+        PosPhrase => "Redewendung",
+        ErrorNoWords => "Keine Wörter für die Übung {0}",
+        ErrorNotEnoughAnswerOptions => "Keine Antwortmöglichkeiten für {0}",
+        ErrorNoArticle => "Kein Artikel für {0}",
+        ErrorNoVerbForm => "Keine Verbform für {0}",
+        ErrorNoExample => "Kein Beispiel für {0}",
+    }
+}
+
+fn template_ru(id: MessageId) -> &'static str {
+    use MessageId::*;
+    match id {
+        TaskSelectDe => "Выберите перевод на немецкий: {0} ({1})",
+        TaskSelectNative => "Выберите перевод ({0}): {1} ({2})",
+        TaskTranslateDe => "Переведите на немецкий: {0} ({1})",
+        TaskTranslateDeHint => "Переведите на немецкий: {0} ({1}). Подсказка: {2}",
+        TaskArticle => "Выберите правильный артикль для существительного: {0}",
+        TaskVerbPresentThird => "Вставьте глагол в настоящем времени: Er ... jetzt [ {0} - {1} ]",
+        TaskVerbPraeteritum => "Вставьте глагол в претеритуме: Er ... einst [ {0} - {1} ]",
+        TaskVerbPerfect => "Вставьте глагол в перфекте: Er ... ... gestern [ {0} - {1} ]",
+        TaskConfusables => "Выберите слово: {0} ({1})",
+        TaskAnagram => "Составьте слово из букв: {0} [ {1} ]",
+        TaskAnagramArticle => "Составьте слово из букв: {0} [ {1} ]. Артикль: {2}",
+        TaskCloze => "Заполните пропуск: {0} [ {1} - {2} ]",
+        TaskMatchPairs => "Сопоставьте слова с переводами",
+        Correct => "Верно!",
+        CorrectDetails => "Верно! {0}",
+        Incorrect => "Неверно! {0}",
+        IncorrectWord => "Неверно! Правильное слово: {0}",
+        IncorrectTranslation => "Неверно! Перевод: {0}",
+        IncorrectArticle => "Неверно! Правильный артикль: {0} - {1}",
+        IncorrectForm => "Неверно! Правильная форма: {0}",
+        HintFirstLetter => "Слово начинается с {0}",
+        HintLength => "{0}",
+        HintArticle => "Артикль: {0}",
+        SelectOneAnswer => "Выберите один ответ",
+        TypeAnswer => "Введите ответ. ß=ss, ö=oe и т. д.",
+        SelectPairs => "Выберите слово, а затем его перевод",
+        SessionFinished => "Сессия завершена",
+        SessionResult => "Правильных ответов: {0} из {1}",
+        Loading => "Загрузка...",
+        WordsInVocabulary => "Слов в словаре: {0}",
+        ButtonStart => "Начать",
+        ButtonSubmit => "Проверить",
+        ButtonNext => "Далее",
+        ButtonHint => "Подсказка",
+        LanguageRu => "русский",
+        LanguageEn => "английский",
+        LanguageUk => "украинский",
+        LanguageDe => "немецкий",
+        PosNoun => "существительное",
+        PosVerb => "глагол",
+        PosAdjective => "прилагательное",
+        PosAdverb => "наречие",
+        PosPreposition => "предлог",
+        PosPronoun => "местоимение",
+        PosConjunction => "союз",
+        PosNumeral => "числительное",
+        PosPhrase => "фраза",
+        ErrorNoWords => "Нет слов для упражнения {0}",
+        ErrorNotEnoughAnswerOptions => "Не удалось подобрать варианты ответа для {0}",
+        ErrorNoArticle => "Нет артикля для {0}",
+        ErrorNoVerbForm => "Нет формы глагола для {0}",
+        ErrorNoExample => "Нет примера для {0}",
+    }
+}
@@ -0,0 +1,245 @@
+use std::collections::VecDeque;
+
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use tsify::Tsify;
+
+use crate::exercise::{ExerciseKey, ExerciseType, Scheduler};
+
+const EXERCISE_SAME_TYPE_COUNT: usize = 10;
+const DEFAULT_CYCLE_LENGTH: usize = 50;
+const DAILY_CHALLENGE_SAME_TYPE_COUNT: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default, Tsify)]
+pub enum SessionOrder {
+    /// Blocks of exercises of the same type, one type after another
+    #[default]
+    Sequential,
+    /// Exercises of all types in random order
+    Shuffle,
+    /// Types take turns one exercise at a time
+    Interleave,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Tsify)]
+pub struct ExercisePlan {
+    pub ex_type: ExerciseType,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    /// Exercises of this type per cycle of the plan
+    #[serde(default)]
+    pub count: Option<usize>,
+    /// Share of this type in a cycle of the plan, used when `count` is not set
+    #[serde(default)]
+    pub ratio: Option<f32>,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Tsify)]
+#[tsify(from_wasm_abi)]
+#[serde(default)]
+pub struct SessionConfig {
+    pub exercises: Vec<ExercisePlan>,
+    pub order: SessionOrder,
+    pub max_exercises: Option<usize>,
+    pub scheduler: Scheduler,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            exercises: ExerciseType::iter()
+                .map(|ex_type| ExercisePlan {
+                    ex_type,
+                    // A new learner has no confusions to practice yet
+                    enabled: ex_type != ExerciseType::Confusables,
+                    count: Some(EXERCISE_SAME_TYPE_COUNT),
+                    ratio: None,
+                })
+                .collect(),
+            order: SessionOrder::Sequential,
+            max_exercises: None,
+            scheduler: Scheduler::Weights,
+        }
+    }
+}
+
+impl SessionConfig {
+    /// Exercises of every type in random order, the same for everyone with the same seed
+    pub fn daily_challenge() -> Self {
+        let mut config = Self {
+            order: SessionOrder::Shuffle,
+            ..Default::default()
+        };
+        // Confusions are personal, so they are not part of the challenge
+        config
+            .exercises
+            .retain(|plan| plan.ex_type != ExerciseType::Confusables);
+        for plan in config.exercises.iter_mut() {
+            plan.count = Some(DAILY_CHALLENGE_SAME_TYPE_COUNT);
+        }
+        config.max_exercises = Some(config.exercises.len() * DAILY_CHALLENGE_SAME_TYPE_COUNT);
+        config
+    }
+
+    fn cycle_counts(&self) -> Vec<(ExerciseType, usize)> {
+        let cycle_length = self.max_exercises.unwrap_or(DEFAULT_CYCLE_LENGTH);
+        self.exercises
+            .iter()
+            .filter(|plan| plan.enabled)
+            .map(|plan| {
+                let count = match (plan.count, plan.ratio) {
+                    (Some(count), _) => count,
+                    (None, Some(ratio)) => ((ratio * cycle_length as f32).round() as usize).max(1),
+                    (None, None) => EXERCISE_SAME_TYPE_COUNT,
+                };
+                (plan.ex_type, count)
+            })
+            .filter(|(_, count)| *count > 0)
+            .collect()
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Tsify)]
+pub struct ExerciseTypeSummary {
+    ex_type: ExerciseType,
+    correct: usize,
+    wrong: usize,
+}
+
+#[derive(Debug, Serialize, Default, Clone, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct SessionSummary {
+    exercises: usize,
+    correct: usize,
+    wrong: usize,
+    by_type: Vec<ExerciseTypeSummary>,
+}
+
+pub struct SessionAnswer {
+    pub word: String,
+    pub key: ExerciseKey,
+    pub correct: bool,
+}
+
+pub struct Session {
+    config: SessionConfig,
+    cycle: Vec<(ExerciseType, usize)>,
+    queue: VecDeque<ExerciseType>,
+    exercises_done: usize,
+    answers: Vec<SessionAnswer>,
+}
+
+impl Session {
+    pub fn new(config: SessionConfig) -> Result<Self, String> {
+        let cycle = config.cycle_counts();
+        if cycle.is_empty() {
+            return Err("No exercise types enabled in the session".to_owned());
+        }
+        Ok(Self {
+            config,
+            cycle,
+            queue: VecDeque::new(),
+            exercises_done: 0,
+            answers: vec![],
+        })
+    }
+
+    pub fn is_type_enabled(&self, ex_type: ExerciseType) -> bool {
+        self.cycle.iter().any(|(t, _)| *t == ex_type)
+    }
+
+    pub fn scheduler(&self) -> Scheduler {
+        self.config.scheduler
+    }
+
+    pub fn is_finished(&self) -> bool {
+        match self.config.max_exercises {
+            Some(max) => self.exercises_done >= max,
+            None => false,
+        }
+    }
+
+    /// Type of the next exercise in the session, `None` when the session is over
+    pub fn next_type(&mut self, rng: &mut StdRng) -> Option<ExerciseType> {
+        if self.is_finished() {
+            return None;
+        }
+        if self.queue.is_empty() {
+            self.fill_queue(rng);
+        }
+        self.queue.pop_front()
+    }
+
+    /// Count the exercise in `max_exercises` once it is created
+    pub fn exercise_created(&mut self) {
+        self.exercises_done += 1;
+    }
+
+    fn fill_queue(&mut self, rng: &mut StdRng) {
+        match self.config.order {
+            SessionOrder::Sequential | SessionOrder::Shuffle => {
+                for (ex_type, count) in self.cycle.iter() {
+                    self.queue.extend(std::iter::repeat_n(*ex_type, *count));
+                }
+                if self.config.order == SessionOrder::Shuffle {
+                    self.queue.make_contiguous().shuffle(rng);
+                }
+            }
+            SessionOrder::Interleave => {
+                let mut remaining = self.cycle.clone();
+                while remaining.iter().any(|(_, count)| *count > 0) {
+                    for (ex_type, count) in remaining.iter_mut().filter(|(_, c)| *c > 0) {
+                        self.queue.push_back(*ex_type);
+                        *count -= 1;
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn add_result(&mut self, word: &str, key: &ExerciseKey, correct: bool) {
+        self.answers.push(SessionAnswer {
+            word: word.to_owned(),
+            key: *key,
+            correct,
+        });
+    }
+
+    pub fn answers(&self) -> &[SessionAnswer] {
+        &self.answers
+    }
+
+    pub fn summary(&self) -> SessionSummary {
+        let mut summary = SessionSummary::default();
+        for answer in self.answers.iter() {
+            let ex_type = answer.key.ex_type;
+            let idx = match summary.by_type.iter().position(|s| s.ex_type == ex_type) {
+                Some(i) => i,
+                None => {
+                    summary.by_type.push(ExerciseTypeSummary {
+                        ex_type,
+                        correct: 0,
+                        wrong: 0,
+                    });
+                    summary.by_type.len() - 1
+                }
+            };
+            let by_type = &mut summary.by_type[idx];
+            if answer.correct {
+                summary.correct += 1;
+                by_type.correct += 1;
+            } else {
+                summary.wrong += 1;
+                by_type.wrong += 1;
+            }
+            summary.exercises += 1;
+        }
+        summary
+    }
+}
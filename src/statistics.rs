@@ -0,0 +1,207 @@
+use serde::Serialize;
+use tsify::Tsify;
+
+use crate::exercise::*;
+use crate::session::Session;
+use crate::words::*;
+
+const HARDEST_WORDS_COUNT: usize = 10;
+const CONFUSIONS_COUNT: usize = 10;
+const MASTERED_SCORE: i32 = 5;
+
+#[derive(Debug, Serialize, Default, Clone, Copy, Tsify)]
+pub struct Accuracy {
+    correct: usize,
+    wrong: usize,
+    accuracy: f32,
+}
+
+impl Accuracy {
+    fn add(&mut self, correct: usize, wrong: usize) {
+        self.correct += correct;
+        self.wrong += wrong;
+        let total = self.correct + self.wrong;
+        if total > 0 {
+            self.accuracy = self.correct as f32 / total as f32;
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Tsify)]
+pub struct ExerciseAccuracy {
+    key: ExerciseKey,
+    accuracy: Accuracy,
+}
+
+#[derive(Debug, Serialize, Tsify)]
+pub struct PosAccuracy {
+    pos: PartOfSpeech,
+    accuracy: Accuracy,
+}
+
+#[derive(Debug, Serialize, Tsify)]
+pub struct GroupAccuracy {
+    group: String,
+    accuracy: Accuracy,
+}
+
+#[derive(Debug, Serialize, Default, Tsify)]
+pub struct AccuracyStatistics {
+    total: Accuracy,
+    by_exercise: Vec<ExerciseAccuracy>,
+    by_pos: Vec<PosAccuracy>,
+    by_group: Vec<GroupAccuracy>,
+}
+
+impl AccuracyStatistics {
+    fn add(&mut self, db: &Database, word: &Word, key: &ExerciseKey, correct: usize, wrong: usize) {
+        self.total.add(correct, wrong);
+
+        match self.by_exercise.iter_mut().find(|s| &s.key == key) {
+            Some(s) => s.accuracy.add(correct, wrong),
+            None => {
+                let mut accuracy = Accuracy::default();
+                accuracy.add(correct, wrong);
+                self.by_exercise.push(ExerciseAccuracy {
+                    key: *key,
+                    accuracy,
+                });
+            }
+        }
+
+        let pos = word.get_pos();
+        match self.by_pos.iter_mut().find(|s| s.pos == pos) {
+            Some(s) => s.accuracy.add(correct, wrong),
+            None => {
+                let mut accuracy = Accuracy::default();
+                accuracy.add(correct, wrong);
+                self.by_pos.push(PosAccuracy { pos, accuracy });
+            }
+        }
+
+        let group = &db.groups[word.get_group_id()];
+        match self.by_group.iter_mut().find(|s| &s.group == group) {
+            Some(s) => s.accuracy.add(correct, wrong),
+            None => {
+                let mut accuracy = Accuracy::default();
+                accuracy.add(correct, wrong);
+                self.by_group.push(GroupAccuracy {
+                    group: group.to_owned(),
+                    accuracy,
+                });
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Tsify)]
+pub struct WordStatistics {
+    id: String,
+    word: String,
+    translation: String,
+    correct: usize,
+    wrong: usize,
+    score: i32,
+}
+
+#[derive(Debug, Serialize, Tsify)]
+pub struct ConfusionStatistics {
+    id: String,
+    word: String,
+    translation: String,
+    confused_with_id: String,
+    confused_with: String,
+    confused_translation: String,
+    count: usize,
+}
+
+#[derive(Debug, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct Statistics {
+    session: AccuracyStatistics,
+    all_time: AccuracyStatistics,
+    hardest_words: Vec<WordStatistics>,
+    mastered_words: usize,
+    learning_words: usize,
+    /// Most frequent confusions first
+    confusions: Vec<ConfusionStatistics>,
+    history: Vec<DayResults>,
+}
+
+pub fn collect_statistics(db: &Database, results: &GameResults, session: &Session) -> Statistics {
+    let mut session_stats = AccuracyStatistics::default();
+    for answer in session.answers() {
+        if let Some(word) = db.words.get(&answer.word) {
+            let (correct, wrong) = if answer.correct { (1, 0) } else { (0, 1) };
+            session_stats.add(db, word, &answer.key, correct, wrong);
+        }
+    }
+
+    let mut all_time = AccuracyStatistics::default();
+    for res in results.results() {
+        if let Some(word) = db.words.get(res.word()) {
+            for ex in res.exercises() {
+                all_time.add(db, word, ex.key(), ex.correct(), ex.wrong());
+            }
+        }
+    }
+
+    let hardest_words = results
+        .get_top_words(HARDEST_WORDS_COUNT)
+        .into_iter()
+        .filter_map(|res| {
+            let word = db.words.get(res.word())?;
+            Some(WordStatistics {
+                id: res.word().to_owned(),
+                word: word.spelling(),
+                translation: word.translation().to_owned(),
+                correct: res.correct(),
+                wrong: res.wrong(),
+                score: res.score(),
+            })
+        })
+        .collect();
+
+    let mastered_words = results
+        .results()
+        .iter()
+        .filter(|res| res.score() >= MASTERED_SCORE || res.status() == WordStatus::Mastered)
+        .filter(|res| db.words.contains_key(res.word()))
+        .count();
+
+    let learning_words = results
+        .results()
+        .iter()
+        .filter(|res| res.stage() == WordStage::Learning && db.words.contains_key(res.word()))
+        .count();
+
+    let mut confusions: Vec<_> = results
+        .confusions()
+        .iter()
+        .filter_map(|c| {
+            let word = db.words.get(c.word())?;
+            let other = db.words.get(c.confused_with())?;
+            Some(ConfusionStatistics {
+                id: c.word().to_owned(),
+                word: word.spelling(),
+                translation: word.translation().to_owned(),
+                confused_with_id: c.confused_with().to_owned(),
+                confused_with: other.spelling(),
+                confused_translation: other.translation().to_owned(),
+                count: c.count(),
+            })
+        })
+        .collect();
+    confusions.sort_by_key(|c| std::cmp::Reverse(c.count));
+    confusions.truncate(CONFUSIONS_COUNT);
+
+    Statistics {
+        session: session_stats,
+        all_time,
+        hardest_words,
+        mastered_words,
+        learning_words,
+        confusions,
+        history: results.history().to_vec(),
+    }
+}
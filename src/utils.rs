@@ -8,3 +8,26 @@ pub fn set_panic_hook() {
     #[cfg(feature = "console_error_panic_hook")]
         console_error_panic_hook::set_once();
 }
+
+/// Current local date as YYYY-MM-DD
+pub fn today() -> String {
+    let date = js_sys::Date::new_0();
+    format!(
+        "{:04}-{:02}-{:02}",
+        date.get_full_year(),
+        date.get_month() + 1,
+        date.get_date()
+    )
+}
+
+/// Current time in seconds
+pub fn now() -> f64 {
+    js_sys::Date::now() / 1000.0
+}
+
+/// FNV-1a hash of the string, stable between runs and platforms
+pub fn hash_str(s: &str) -> u64 {
+    s.bytes().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ b as u64).wrapping_mul(0x100000001b3)
+    })
+}
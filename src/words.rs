@@ -1,14 +1,45 @@
 use std::{collections::HashMap, fmt::Display};
 
+use calamine::{DataType, Range};
+
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
+use tsify::Tsify;
 
-#[derive(Debug, Clone, Copy, EnumIter, PartialEq)]
+#[derive(Debug, Clone, Copy, EnumIter, PartialEq, Serialize, Deserialize, Tsify)]
 pub enum PartOfSpeech {
     Noun,
     Verb,
     Adjective,
     Adverb,
     Preposition,
+    Pronoun,
+    Conjunction,
+    Numeral,
+    /// Idiomatic phrase of several words
+    Phrase,
+}
+
+impl PartOfSpeech {
+    /// Code of the PoS column of the sheet
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Noun => "n",
+            Self::Verb => "v",
+            Self::Adjective => "adj",
+            Self::Adverb => "adv",
+            Self::Preposition => "prep",
+            Self::Pronoun => "pron",
+            Self::Conjunction => "conj",
+            Self::Numeral => "num",
+            Self::Phrase => "phrase",
+        }
+    }
+
+    pub fn from_code(code: &str) -> Option<Self> {
+        Self::iter().find(|pos| pos.code() == code)
+    }
 }
 
 fn umlaut_normalize(word: &str) -> String {
@@ -28,6 +59,40 @@ pub fn check_spelling_simple(answer: &str, expected: &str) -> bool {
     }
 }
 
+/// Phrases are compared word by word, ignoring the punctuation and extra spaces,
+/// "geht's" matches "gehts"
+pub fn check_spelling_phrase(answer: &str, expected: &str) -> bool {
+    let words = |s: &str| -> Vec<String> {
+        s.replace(['\'', '’'], "")
+            .split(|c: char| c.is_whitespace() || (c.is_ascii_punctuation() && c != '/'))
+            .filter(|w| !w.is_empty())
+            .map(|w| w.to_owned())
+            .collect()
+    };
+    let answer = words(answer);
+    let expected = words(expected);
+    answer.len() == expected.len()
+        && answer
+            .iter()
+            .zip(expected.iter())
+            .all(|(a, e)| check_spelling_simple(a, e))
+}
+
+/// Levenshtein distance between the words in characters
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut cur = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let subst = prev[j] + usize::from(ca != *cb);
+            cur[j + 1] = subst.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        prev = cur;
+    }
+    prev[b.len()]
+}
+
 pub fn check_spelling_perfect(answer: &str, expected: &str) -> bool {
     let mut frags = answer.split_whitespace();
     let first = match frags.next() {
@@ -51,67 +116,94 @@ pub fn check_spelling_perfect(answer: &str, expected: &str) -> bool {
     check_spelling_simple(second, expected)
 }
 
-pub trait Word {
-    fn pos_str(&self) -> &'static str {
-        unimplemented!()
-    }
-
-    fn translation(&self) -> &str;
-
-    fn spelling(&self) -> String {
-        self.get_word().to_owned()
-    }
-
-    fn check_spelling(&self, answer: &str) -> bool {
-        check_spelling_simple(answer, &self.spelling())
-    }
-
-    fn get_word(&self) -> &str;
-
-    fn new(map: &mut HashMap<usize, String>, db: &mut Database) -> Self
-    where
-        Self: Sized;
-
-    fn get_help(&self) -> &str;
-
-    fn get_group_id(&self) -> usize;
-
-    fn get_pos(&self) -> PartOfSpeech;
-
-    fn get_article(&self) -> Option<NounArticle> {
-        None
-    }
-
-    fn get_verb_praeteritum(&self) -> Option<&str> {
-        None
-    }
-
-    fn get_verb_perfect(&self) -> Option<&str> {
-        None
-    }
+/// Word of the vocabulary, the data of its part of speech is in `kind`.
+/// Serialized as a word of `VocabularyJson`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Word {
+    pub id: String,
+    pub word: String,
+    /// Index in `Database::groups`
+    #[serde(rename = "group")]
+    pub group_id: usize,
+    /// Translations to the languages of `Database::languages`, empty if missing
+    pub translations: Vec<String>,
+    /// Index of the native language in `Database::languages`
+    #[serde(skip)]
+    pub language: usize,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub help: String,
+    /// Example sentences with the word marked as `*word*`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub examples: Vec<String>,
+    #[serde(flatten)]
+    pub kind: WordKind,
+}
 
-    fn get_verb_perfect_verb(&self) -> Option<&PerfectVerb> {
-        None
-    }
+/// Data specific to the part of speech, tagged with the code of the PoS column
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "pos")]
+pub enum WordKind {
+    #[serde(rename = "n")]
+    Noun { article: NounArticle },
+    #[serde(rename = "v")]
+    Verb(VerbForms),
+    #[serde(rename = "adj")]
+    Adjective,
+    #[serde(rename = "adv")]
+    Adverb,
+    #[serde(rename = "prep")]
+    Preposition,
+    #[serde(rename = "pron")]
+    Pronoun,
+    #[serde(rename = "conj")]
+    Conjunction,
+    #[serde(rename = "num")]
+    Numeral,
+    #[serde(rename = "phrase")]
+    Phrase,
+}
 
-    fn get_verb_perfect_full(&self) -> Option<String> {
-        None
-    }
+/// Forms of the verb, empty if missing in the sheet
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct VerbForms {
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub praeteritum: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub perfect: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub perfect_verb: Option<PerfectVerb>,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub present_third: String,
+}
 
-    fn get_verb_present_third(&self) -> Option<&str> {
-        None
+fn non_empty(s: &str) -> Option<&str> {
+    match s {
+        "" => None,
+        _ => Some(s),
     }
 }
 
-#[derive(Debug, EnumIter, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, EnumIter, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum NounArticle {
     Der,
     Das,
     Die,
+    #[serde(rename = "pl")]
     Plural,
 }
 
 impl NounArticle {
+    /// Code of the article column of the sheet
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Plural => "pl",
+            Self::Der => "der",
+            Self::Das => "das",
+            Self::Die => "die",
+        }
+    }
+
     pub fn answer_bullet_str(&self) -> String {
         match self {
             Self::Plural => "die (plural)".to_string(),
@@ -132,146 +224,244 @@ impl Display for NounArticle {
     }
 }
 
-#[derive(Debug)]
-pub struct WordCommon {
-    pub word: String,
-    pub group_id: usize,
-    pub translation: String,
-    pub help: String,
+/// Language of the translation column, other languages are in the columns "Translation (code)"
+pub const DEFAULT_LANGUAGE: &str = "ru";
+
+pub(crate) const WORD_IDX: usize = 0;
+pub(crate) const POS_IDX: usize = 1;
+pub(crate) const TRANSLATION_IDX: usize = 2;
+pub(crate) const GROUP_IDX: usize = 3;
+pub(crate) const ARTICLE_IDX: usize = 4;
+pub(crate) const HELP_IDX: usize = 7;
+pub(crate) const PERFECT_IDX: usize = 5;
+pub(crate) const PRAETERITUM_IDX: usize = 6;
+pub(crate) const PERFECT_VERB_IDX: usize = 8;
+pub(crate) const PRESENT_THIRD_IDX: usize = 9;
+/// One example sentence per line
+pub(crate) const EXAMPLES_IDX: usize = 10;
+
+/// Id of the word from the PoS code, the article of a noun and the word, e.g. "n:die:Leiter",
+/// so homographs do not overwrite each other
+pub fn word_id(word: &str, pos: &str, article: Option<&str>) -> String {
+    match article {
+        Some(article) => format!("{}:{}:{}", pos, article, word),
+        None => format!("{}:{}", pos, word),
+    }
 }
 
-const WORD_IDX: usize = 0;
-const POS_IDX: usize = 1;
-const TRANSLATION_IDX: usize = 2;
-const GROUP_IDX: usize = 3;
-const ARTICLE_IDX: usize = 4;
-const HELP_IDX: usize = 7;
-const PERFECT_IDX: usize = 5;
-const PRAETERITUM_IDX: usize = 6;
-const PERFECT_VERB_IDX: usize = 8;
-const PRESENT_THIRD_IDX: usize = 9;
-
 pub fn get_part_of_speech(map: &HashMap<usize, String>) -> &str {
-    &map[&POS_IDX]
+    map.get(&POS_IDX).map(|s| s.trim()).unwrap_or_default()
 }
 
-impl Word for WordCommon {
-    fn new(map: &mut HashMap<usize, String>, db: &mut Database) -> Self {
-        Self {
-            word: map.remove(&WORD_IDX).unwrap(),
-            group_id: db.get_group_id(&map.remove(&GROUP_IDX).unwrap()),
-            translation: map.remove(&TRANSLATION_IDX).unwrap(),
-            help: map.remove(&HELP_IDX).unwrap(),
+impl Word {
+    /// Read the word from a row of the sheet given as the cells by column
+    pub fn new(map: &mut HashMap<usize, String>, db: &mut Database) -> Result<Self, String> {
+        let word = map.remove(&WORD_IDX).unwrap_or_default();
+        let code = get_part_of_speech(map).to_owned();
+        let pos = PartOfSpeech::from_code(&code)
+            .ok_or_else(|| format!("Unknown part of speech {:?} of the word {}", code, word))?;
+        let explicit_id = db
+            .id_column
+            .and_then(|idx| map.remove(&idx))
+            .map(|s| s.trim().to_owned());
+        let article = match pos {
+            PartOfSpeech::Noun => map.remove(&ARTICLE_IDX).map(|s| s.trim().to_owned()),
+            _ => None,
+        };
+        let id = match explicit_id {
+            Some(id) if !id.is_empty() => id,
+            _ => word_id(word.trim(), &code, article.as_deref()),
+        };
+        let mut form = |idx| map.remove(&idx).unwrap_or_default();
+        let kind = match pos {
+            PartOfSpeech::Noun => WordKind::Noun {
+                article: get_article(article.as_deref().unwrap_or_default())?,
+            },
+            PartOfSpeech::Verb => WordKind::Verb(VerbForms {
+                praeteritum: form(PRAETERITUM_IDX),
+                perfect: form(PERFECT_IDX),
+                perfect_verb: PerfectVerb::parse(&form(PERFECT_VERB_IDX))?,
+                present_third: form(PRESENT_THIRD_IDX),
+            }),
+            PartOfSpeech::Adjective => WordKind::Adjective,
+            PartOfSpeech::Adverb => WordKind::Adverb,
+            PartOfSpeech::Preposition => WordKind::Preposition,
+            PartOfSpeech::Pronoun => WordKind::Pronoun,
+            PartOfSpeech::Conjunction => WordKind::Conjunction,
+            PartOfSpeech::Numeral => WordKind::Numeral,
+            PartOfSpeech::Phrase => WordKind::Phrase,
+        };
+        Ok(Self {
+            id,
+            group_id: db.get_group_id(&map.remove(&GROUP_IDX).unwrap_or_default()),
+            translations: db
+                .translation_columns
+                .iter()
+                .map(|idx| map.remove(idx).unwrap_or_default().trim().to_owned())
+                .collect(),
+            language: db.language,
+            help: map.remove(&HELP_IDX).unwrap_or_default(),
+            examples: map
+                .remove(&EXAMPLES_IDX)
+                .unwrap_or_default()
+                .lines()
+                .map(|l| l.trim().to_owned())
+                .filter(|l| !l.is_empty())
+                .collect(),
+            word,
+            kind,
+        })
+    }
+
+    /// Translation to the native language of the learner, see `Database::set_native_language`.
+    /// Falls back to the default language if there is no translation
+    pub fn translation(&self) -> &str {
+        match self.translations.get(self.language) {
+            Some(t) if !t.is_empty() => t,
+            _ => self.translations.first().map_or("", |t| t),
         }
     }
 
-    fn translation(&self) -> &str {
-        &self.translation
+    pub fn set_language(&mut self, language: usize) {
+        self.language = language;
     }
 
-    fn get_word(&self) -> &str {
-        &self.word
+    pub fn get_translations(&self) -> &[String] {
+        &self.translations
     }
 
-    fn get_help(&self) -> &str {
-        &self.help
+    pub fn spelling(&self) -> String {
+        match &self.kind {
+            WordKind::Noun { article } => article.to_string() + " " + &capitalize_noun(&self.word),
+            _ => self.word.clone(),
+        }
     }
 
-    fn get_group_id(&self) -> usize {
-        self.group_id
+    pub fn get_word(&self) -> &str {
+        &self.word
     }
 
-    fn get_pos(&self) -> PartOfSpeech {
-        unimplemented!()
+    /// Unique key of the word in the database and the results, see `word_id`
+    pub fn get_id(&self) -> &str {
+        &self.id
     }
-}
 
-fn get_article(s: &str) -> Result<NounArticle, String> {
-    Ok(match s {
-        "der" => NounArticle::Der,
-        "das" => NounArticle::Das,
-        "die" => NounArticle::Die,
-        "pl" => NounArticle::Plural,
-        _ => return Err(format!("Unknown article {:?}", s)),
-    })
-}
+    pub fn get_help(&self) -> &str {
+        &self.help
+    }
 
-pub fn capitalize_noun(noun: &str) -> String {
-    noun.chars().next().unwrap().to_uppercase().to_string()
-        + &noun.chars().skip(1).collect::<String>()
-}
+    pub fn get_examples(&self) -> &[String] {
+        &self.examples
+    }
 
-#[derive(Debug)]
-pub struct Noun {
-    pub common: WordCommon,
-    pub article: NounArticle,
-}
+    pub fn get_group_id(&self) -> usize {
+        self.group_id
+    }
 
-impl Word for Noun {
-    fn pos_str(&self) -> &'static str {
-        "noun"
+    pub fn get_pos(&self) -> PartOfSpeech {
+        match self.kind {
+            WordKind::Noun { .. } => PartOfSpeech::Noun,
+            WordKind::Verb(_) => PartOfSpeech::Verb,
+            WordKind::Adjective => PartOfSpeech::Adjective,
+            WordKind::Adverb => PartOfSpeech::Adverb,
+            WordKind::Preposition => PartOfSpeech::Preposition,
+            WordKind::Pronoun => PartOfSpeech::Pronoun,
+            WordKind::Conjunction => PartOfSpeech::Conjunction,
+            WordKind::Numeral => PartOfSpeech::Numeral,
+            WordKind::Phrase => PartOfSpeech::Phrase,
+        }
     }
 
-    fn spelling(&self) -> String {
-        self.article.to_string() + " " + &capitalize_noun(&self.common.word)
+    pub fn get_article(&self) -> Option<NounArticle> {
+        match self.kind {
+            WordKind::Noun { article } => Some(article),
+            _ => None,
+        }
     }
 
-    fn translation(&self) -> &str {
-        self.common.translation()
+    pub fn get_verb_forms(&self) -> Option<&VerbForms> {
+        match &self.kind {
+            WordKind::Verb(forms) => Some(forms),
+            _ => None,
+        }
     }
 
-    fn get_word(&self) -> &str {
-        self.common.get_word()
+    pub fn get_verb_praeteritum(&self) -> Option<&str> {
+        non_empty(&self.get_verb_forms()?.praeteritum)
     }
 
-    fn get_help(&self) -> &str {
-        self.common.get_help()
+    pub fn get_verb_perfect(&self) -> Option<&str> {
+        non_empty(&self.get_verb_forms()?.perfect)
     }
 
-    fn new(map: &mut HashMap<usize, String>, db: &mut Database) -> Self {
-        Self {
-            common: WordCommon::new(map, db),
-            article: get_article(&map.remove(&ARTICLE_IDX).unwrap()).unwrap(),
-        }
+    pub fn get_verb_perfect_verb(&self) -> Option<&PerfectVerb> {
+        self.get_verb_forms()?.perfect_verb.as_ref()
     }
 
-    fn get_group_id(&self) -> usize {
-        self.common.get_group_id()
+    pub fn get_verb_perfect_full(&self) -> Option<String> {
+        Some(format!(
+            "{} {}",
+            self.get_verb_perfect_verb()?,
+            self.get_verb_perfect()?
+        ))
     }
 
-    fn get_pos(&self) -> PartOfSpeech {
-        PartOfSpeech::Noun
+    pub fn get_verb_present_third(&self) -> Option<&str> {
+        non_empty(&self.get_verb_forms()?.present_third)
     }
+}
+
+fn get_article(s: &str) -> Result<NounArticle, String> {
+    Ok(match s {
+        "der" => NounArticle::Der,
+        "das" => NounArticle::Das,
+        "die" => NounArticle::Die,
+        "pl" => NounArticle::Plural,
+        _ => return Err(format!("Unknown article {:?}", s)),
+    })
+}
+
+/// Sentence with the marked word replaced by a gap and the marked word,
+/// `None` if nothing is marked
+pub fn cloze_sentence(example: &str) -> Option<(String, String)> {
+    let start = example.find('*')?;
+    let len = example[start + 1..].find('*')?;
+    let answer = &example[start + 1..start + 1 + len];
+    if answer.trim().is_empty() {
+        return None;
+    }
+    let gap = format!("{}___{}", &example[..start], &example[start + len + 2..]);
+    Some((gap.replace('*', ""), answer.trim().to_owned()))
+}
 
-    fn get_article(&self) -> Option<NounArticle> {
-        Some(self.article)
+pub fn capitalize_noun(noun: &str) -> String {
+    let mut chars = noun.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PerfectVerb {
+    #[serde(rename = "hat")]
     Haben,
+    #[serde(rename = "ist")]
     Sein,
+    #[serde(rename = "hat/ist")]
     Both,
 }
 
 impl PerfectVerb {
-    pub fn from(s: &str) -> Option<Self> {
-        Some(match s.trim() {
+    /// `None` if the cell is empty
+    pub fn parse(s: &str) -> Result<Option<Self>, String> {
+        Ok(Some(match s.trim() {
             "hat" => PerfectVerb::Haben,
             "ist" => PerfectVerb::Sein,
             "hat/ist" => PerfectVerb::Both,
-            s if s.is_empty() => {
-                return None;
-            }
-            _ => {
-                panic!("Unknown perfect verb {}", s);
-            }
-        })
-    }
-
-    pub fn from_option(s: Option<String>) -> Option<Self> {
-        Self::from(&s?)
+            "" => return Ok(None),
+            _ => return Err(format!("Unknown perfect verb {:?}", s)),
+        }))
     }
 }
 
@@ -286,214 +476,362 @@ impl Display for PerfectVerb {
     }
 }
 
-#[derive(Debug)]
-pub struct Verb {
-    pub common: WordCommon,
-    pub praeteritum: String,
-    pub perfect: String,
-    pub perfect_verb: Option<PerfectVerb>,
-    pub present_third: String,
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize, Tsify)]
+pub enum MatchMode {
+    Prefix,
+    #[default]
+    Substring,
 }
 
-impl Word for Verb {
-    fn pos_str(&self) -> &'static str {
-        "verb"
-    }
-
-    fn new(map: &mut HashMap<usize, String>, db: &mut Database) -> Self {
-        Self {
-            common: WordCommon::new(map, db),
-            praeteritum: map.remove(&PRAETERITUM_IDX).unwrap_or_default(),
-            perfect: map.remove(&PERFECT_IDX).unwrap_or_default(),
-            perfect_verb: PerfectVerb::from_option(map.remove(&PERFECT_VERB_IDX)),
-            present_third: map.remove(&PRESENT_THIRD_IDX).unwrap_or_default(),
-        }
-    }
-
-    fn translation(&self) -> &str {
-        self.common.translation()
-    }
-
-    fn get_word(&self) -> &str {
-        self.common.get_word()
-    }
-
-    fn get_help(&self) -> &str {
-        self.common.get_help()
-    }
-
-    fn get_group_id(&self) -> usize {
-        self.common.get_group_id()
-    }
-
-    fn get_pos(&self) -> PartOfSpeech {
-        PartOfSpeech::Verb
-    }
-
-    fn get_verb_praeteritum(&self) -> Option<&str> {
-        if self.praeteritum.is_empty() {
-            return None;
-        }
-        Some(&self.praeteritum)
-    }
-
-    fn get_verb_present_third(&self) -> Option<&str> {
-        if self.present_third.is_empty() {
-            return None;
-        }
-        Some(&self.present_third)
-    }
-
-    fn get_verb_perfect_verb(&self) -> Option<&PerfectVerb> {
-        self.perfect_verb.as_ref()
-    }
-
-    fn get_verb_perfect(&self) -> Option<&str> {
-        if self.perfect.is_empty() {
-            return None;
-        }
-        Some(&self.perfect)
-    }
-
-    fn get_verb_perfect_full(&self) -> Option<String> {
-        Some(format!(
-            "{} {}",
-            self.get_verb_perfect_verb()?,
-            self.get_verb_perfect()?
-        ))
-    }
+/// Search in the German words and all translations, ß and umlauts match ss, ue etc.
+#[derive(Debug, Default, Deserialize, Tsify)]
+#[tsify(from_wasm_abi)]
+#[serde(default)]
+pub struct WordQuery {
+    pub text: String,
+    pub mode: MatchMode,
+    pub pos: Option<PartOfSpeech>,
+    /// Group ids, all groups if empty
+    pub groups: Vec<usize>,
+    pub limit: Option<usize>,
 }
 
-#[derive(Debug)]
-pub struct Adjective {
-    pub common: WordCommon,
+/// Lowercase text with the umlauts written out as "ue" and as the plain vowel,
+/// so both "gruessen" and "grussen" find "grüßen"
+fn search_normalize(text: &str) -> [String; 2] {
+    let text = text.trim().to_lowercase();
+    let plain = text
+        .replace('ü', "u")
+        .replace('ä', "a")
+        .replace('ö', "o")
+        .replace('ß', "ss");
+    [umlaut_normalize(&text), plain]
 }
 
-impl Word for Adjective {
-    fn pos_str(&self) -> &'static str {
-        "adj"
-    }
-
-    fn new(map: &mut HashMap<usize, String>, db: &mut Database) -> Self {
-        Self {
-            common: WordCommon::new(map, db),
-        }
-    }
-
-    fn translation(&self) -> &str {
-        self.common.translation()
-    }
-
-    fn get_word(&self) -> &str {
-        self.common.get_word()
-    }
-
-    fn get_help(&self) -> &str {
-        self.common.get_help()
-    }
-
-    fn get_group_id(&self) -> usize {
-        self.common.get_group_id()
-    }
-
-    fn get_pos(&self) -> PartOfSpeech {
-        PartOfSpeech::Adjective
-    }
+#[derive(Debug, Serialize, Tsify)]
+pub struct TranslationCard {
+    pub language: String,
+    pub translation: String,
 }
 
-#[derive(Debug)]
-pub struct Adverb {
-    pub common: WordCommon,
+/// Everything known about the word
+#[derive(Debug, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct WordCard {
+    pub id: String,
+    pub word: String,
+    pub spelling: String,
+    pub pos: PartOfSpeech,
+    pub group: String,
+    pub group_id: usize,
+    pub translation: String,
+    pub translations: Vec<TranslationCard>,
+    pub article: Option<String>,
+    pub help: String,
+    pub examples: Vec<String>,
+    pub praeteritum: Option<String>,
+    pub perfect: Option<String>,
+    pub present_third: Option<String>,
 }
 
-impl Word for Adverb {
-    fn pos_str(&self) -> &'static str {
-        "adv"
-    }
-
-    fn new(map: &mut HashMap<usize, String>, db: &mut Database) -> Self {
+impl WordCard {
+    pub fn new(db: &Database, word: &Word) -> Self {
         Self {
-            common: WordCommon::new(map, db),
+            id: word.get_id().to_owned(),
+            word: word.get_word().to_owned(),
+            spelling: word.spelling(),
+            pos: word.get_pos(),
+            group: db
+                .groups
+                .get(word.get_group_id())
+                .cloned()
+                .unwrap_or_default(),
+            group_id: word.get_group_id(),
+            translation: word.translation().to_owned(),
+            translations: db
+                .languages
+                .iter()
+                .zip(word.get_translations())
+                .filter(|(_, t)| !t.is_empty())
+                .map(|(language, translation)| TranslationCard {
+                    language: language.to_owned(),
+                    translation: translation.to_owned(),
+                })
+                .collect(),
+            article: word.get_article().map(|a| a.to_string()),
+            help: word.get_help().to_owned(),
+            examples: word.get_examples().to_vec(),
+            praeteritum: word.get_verb_praeteritum().map(|s| s.to_owned()),
+            perfect: word.get_verb_perfect_full(),
+            present_third: word.get_verb_present_third().map(|s| s.to_owned()),
         }
     }
+}
 
-    fn translation(&self) -> &str {
-        self.common.translation()
-    }
+#[derive(Debug, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct WordSearch {
+    pub words: Vec<WordCard>,
+}
 
-    fn get_word(&self) -> &str {
-        self.common.get_word()
-    }
+pub struct Database {
+    pub groups: Vec<String>,
+    /// Words by id
+    pub words: HashMap<String, Word>,
+    /// Word ids in the order of the sheet
+    pub order: Vec<String>,
+    /// Problems found while loading the words, e.g. duplicates
+    pub warnings: Vec<String>,
+    /// Codes of the translation languages, `DEFAULT_LANGUAGE` first
+    pub languages: Vec<String>,
+    translation_columns: Vec<usize>,
+    /// Optional column "Id" with explicit word ids
+    id_column: Option<usize>,
+    /// Native language of the learner
+    language: usize,
+}
 
-    fn get_help(&self) -> &str {
-        self.common.get_help()
-    }
+/// Version of `VocabularyJson`, increased on incompatible changes
+pub const VOCABULARY_VERSION: u32 = 1;
+
+/// Vocabulary preprocessed from the sheet, the fields of the words are described in `Word`,
+/// empty fields are omitted, e.g.
+/// `{"version": 1, "languages": ["ru", "en"], "groups": ["Alltag"], "words": [
+/// {"id": "n:der:Gruß", "pos": "n", "word": "Gruß", "group": 0, "translations": ["привет", "greeting"],
+/// "article": "der"}]}`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VocabularyJson {
+    pub version: u32,
+    /// Codes of the translation languages, `DEFAULT_LANGUAGE` first
+    pub languages: Vec<String>,
+    pub groups: Vec<String>,
+    /// Words in the order of the sheet
+    pub words: Vec<Word>,
+}
 
-    fn get_group_id(&self) -> usize {
-        self.common.get_group_id()
+impl Default for Database {
+    fn default() -> Self {
+        Self::new()
     }
+}
 
-    fn get_pos(&self) -> PartOfSpeech {
-        PartOfSpeech::Adverb
-    }
+#[derive(Debug, Serialize, Tsify)]
+pub struct GroupInfo {
+    pub id: usize,
+    pub name: String,
+    pub words: usize,
 }
 
-#[derive(Debug)]
-pub struct Preposition {
-    pub common: WordCommon,
+#[derive(Debug, Serialize, Tsify)]
+#[tsify(into_wasm_abi)]
+pub struct GroupList {
+    pub groups: Vec<GroupInfo>,
 }
 
-impl Word for Preposition {
-    fn pos_str(&self) -> &'static str {
-        "preposition"
+impl Database {
+    pub fn get_group_id(&mut self, name: &str) -> usize {
+        match self.groups.iter().position(|g| g == name) {
+            None => {
+                self.groups.push(name.to_owned());
+                self.groups.len() - 1
+            }
+            Some(i) => i,
+        }
     }
 
-    fn new(map: &mut HashMap<usize, String>, db: &mut Database) -> Self {
-        Self {
-            common: WordCommon::new(map, db),
+    /// Find the translation columns "Translation (code)" and the "Id" column in the header row
+    pub fn read_header(&mut self, header: &[String]) {
+        for (idx, title) in header.iter().enumerate() {
+            if title.trim() == "Id" {
+                self.id_column = Some(idx);
+                continue;
+            }
+            let code = title
+                .trim()
+                .strip_prefix("Translation (")
+                .and_then(|s| s.strip_suffix(')'))
+                .map(|s| s.trim().to_lowercase());
+            match code {
+                Some(code) if !self.languages.contains(&code) => {
+                    self.languages.push(code);
+                    self.translation_columns.push(idx);
+                }
+                _ => (),
+            }
         }
     }
 
-    fn translation(&self) -> &str {
-        self.common.translation()
+    pub fn native_language(&self) -> &str {
+        &self.languages[self.language]
     }
 
-    fn get_word(&self) -> &str {
-        self.common.get_word()
+    pub fn set_native_language(&mut self, code: &str) -> Result<(), String> {
+        let language = self
+            .languages
+            .iter()
+            .position(|l| l == code)
+            .ok_or_else(|| format!("No translations to {}", code))?;
+        self.language = language;
+        for word in self.words.values_mut() {
+            word.set_language(language);
+        }
+        Ok(())
+    }
+
+    /// The first one of the words with the same id is kept, the others are reported as warnings
+    pub fn add_word(&mut self, mut word: Word) {
+        word.set_language(self.language);
+        let key = word.get_id().to_owned();
+        if self.words.contains_key(&key) {
+            self.warnings.push(format!(
+                "Duplicate word {} with id {}, only the first one is used",
+                word.get_word(),
+                key
+            ));
+            return;
+        }
+        self.words.insert(key.clone(), word);
+        self.order.push(key);
+    }
+
+    /// Words in the order of the sheet, unlike `words` it is the same on every run
+    pub fn words_in_order(&self) -> impl Iterator<Item = &Word> {
+        self.order.iter().filter_map(|key| self.words.get(key))
+    }
+
+    /// Words in the order to introduce them to the learner: by group, then as in the sheet
+    pub fn words_in_learning_order(&self) -> Vec<&Word> {
+        let mut words: Vec<_> = self.words_in_order().collect();
+        words.sort_by_key(|w| w.get_group_id());
+        words
+    }
+
+    pub fn get_groups_info(&self) -> Vec<GroupInfo> {
+        self.groups
+            .iter()
+            .enumerate()
+            .map(|(id, name)| GroupInfo {
+                id,
+                name: name.to_owned(),
+                words: self
+                    .words
+                    .values()
+                    .filter(|w| w.get_group_id() == id)
+                    .count(),
+            })
+            .collect()
+    }
+
+    /// Words matching the query, the ones starting with the text first, then as in the sheet
+    pub fn search(&self, query: &WordQuery) -> Vec<&Word> {
+        let texts = search_normalize(&query.text);
+        let mut found: Vec<(bool, &Word)> = self
+            .words_in_order()
+            .filter(|w| query.pos.is_none_or(|pos| w.get_pos() == pos))
+            .filter(|w| query.groups.is_empty() || query.groups.contains(&w.get_group_id()))
+            .filter_map(|w| {
+                let fields: Vec<[String; 2]> = std::iter::once(w.get_word())
+                    .chain(w.get_translations().iter().map(|t| t.as_str()))
+                    .map(search_normalize)
+                    .collect();
+                let matches = |is_match: fn(&str, &str) -> bool| {
+                    fields
+                        .iter()
+                        .any(|forms| forms.iter().zip(&texts).any(|(f, t)| is_match(f, t)))
+                };
+                match query.mode {
+                    _ if matches(|f, t| f.starts_with(t)) => Some((true, w)),
+                    MatchMode::Substring if matches(|f, t| f.contains(t)) => Some((false, w)),
+                    _ => None,
+                }
+            })
+            .collect();
+        found.sort_by_key(|(prefix, _)| !prefix);
+        if let Some(limit) = query.limit {
+            found.truncate(limit);
+        }
+        found.into_iter().map(|(_, w)| w).collect()
     }
 
-    fn get_help(&self) -> &str {
-        self.common.get_help()
-    }
+    /// Read the words from the sheet "Words": the header in the second row, then a word per row
+    pub fn read_sheet(&mut self, range: &Range<DataType>) {
+        if let Some(header) = range.rows().nth(1) {
+            let header: Vec<String> = header.iter().map(|dt| dt.to_string()).collect();
+            self.read_header(&header);
+        }
 
-    fn get_group_id(&self) -> usize {
-        self.common.get_group_id()
+        // The range starts at the first used cell of the sheet, rows are reported as numbered there
+        let first_row = range.start().map_or(0, |(row, _)| row as usize);
+        for (i, row) in range.rows().enumerate().skip(2) {
+            let mut map: HashMap<usize, String> =
+                row.iter().map(|dt| dt.to_string()).enumerate().collect();
+            if map.values().all(|cell| cell.trim().is_empty()) {
+                continue;
+            }
+            match Word::new(&mut map, self) {
+                Ok(word) => self.add_word(word),
+                Err(e) => self
+                    .warnings
+                    .push(format!("Row {}: {}", first_row + i + 1, e)),
+            }
+        }
     }
 
-    fn get_pos(&self) -> PartOfSpeech {
-        PartOfSpeech::Preposition
+    /// Vocabulary in the format of `VocabularyJson`
+    pub fn to_json(&self) -> String {
+        let vocabulary = VocabularyJson {
+            version: VOCABULARY_VERSION,
+            languages: self.languages.clone(),
+            groups: self.groups.clone(),
+            words: self.words_in_order().cloned().collect(),
+        };
+        serde_json::to_string(&vocabulary).unwrap_or_default()
     }
-}
-pub struct Database {
-    pub groups: Vec<String>,
-    pub words: HashMap<String, Box<dyn Word>>,
-}
 
-impl Database {
-    pub fn get_group_id(&mut self, name: &str) -> usize {
-        match self.groups.iter().position(|g| g == name) {
-            None => {
-                self.groups.push(name.to_owned());
-                self.groups.len() - 1
+    /// Load the vocabulary saved by `to_json`
+    pub fn from_json(json: &str) -> Result<Self, String> {
+        let vocabulary: VocabularyJson = serde_json::from_str(json).map_err(|e| e.to_string())?;
+        if vocabulary.version != VOCABULARY_VERSION {
+            return Err(format!(
+                "Unsupported vocabulary version {}",
+                vocabulary.version
+            ));
+        }
+        if vocabulary.languages.is_empty() {
+            return Err("No languages in the vocabulary".to_owned());
+        }
+        let mut db = Database::new();
+        db.translation_columns.clear();
+        db.languages = vocabulary.languages;
+        db.groups = vocabulary.groups;
+        for mut word in vocabulary.words {
+            word.translations.resize(db.languages.len(), String::new());
+            if word.group_id >= db.groups.len() {
+                return Err(format!(
+                    "Unknown group {} of the word {}",
+                    word.group_id, word.word
+                ));
             }
-            Some(i) => i,
+            db.add_word(word);
         }
+        Ok(db)
+    }
+
+    /// New groups are appended at the end of the sheet
+    pub fn get_latest_group_id(&self) -> Option<usize> {
+        self.groups.len().checked_sub(1)
     }
 
     pub fn new() -> Database {
         Database {
             groups: vec![],
             words: HashMap::new(),
+            order: vec![],
+            warnings: vec![],
+            languages: vec![DEFAULT_LANGUAGE.to_owned()],
+            translation_columns: vec![TRANSLATION_IDX],
+            id_column: None,
+            language: 0,
         }
     }
 }
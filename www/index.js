@@ -6,11 +6,75 @@ init_wasm_logging();
 
 const game = WordsGame.create();
 
+const RESULTS_KEY = 'dw-web-results';
+const LANGUAGE_KEY = 'dw-web-language';
+const UI_LANGUAGE_KEY = 'dw-web-ui-language';
+
+const loadResults = () => {
+    const saved = window.localStorage.getItem(RESULTS_KEY);
+    if (saved) {
+        try {
+            game.import_results(saved);
+        } catch (error) {
+            console.error('Failed to load saved results');
+            console.error(error);
+        }
+    }
+}
+
+const saveResults = () => {
+    window.localStorage.setItem(RESULTS_KEY, game.export_results());
+}
+
+loadResults();
+
+// Native language from the "lang" parameter of the page or the previous visit
+const applyNativeLanguage = () => {
+    const language = new URLSearchParams(window.location.search).get('lang')
+        || window.localStorage.getItem(LANGUAGE_KEY);
+    if (!language) {
+        return;
+    }
+    try {
+        game.set_native_language(language);
+        window.localStorage.setItem(LANGUAGE_KEY, language);
+    } catch (error) {
+        console.error('Failed to set native language ' + language);
+        console.error(error);
+    }
+}
+
+// Interface language from the "ui" parameter of the page or the previous visit
+const applyUiLanguage = () => {
+    const language = new URLSearchParams(window.location.search).get('ui')
+        || window.localStorage.getItem(UI_LANGUAGE_KEY);
+    if (!language) {
+        return;
+    }
+    try {
+        game.set_ui_language(language);
+        window.localStorage.setItem(UI_LANGUAGE_KEY, language);
+    } catch (error) {
+        console.error('Failed to set interface language ' + language);
+        console.error(error);
+    }
+}
+
+applyUiLanguage();
+
+const message = (id, ...params) => game.get_message(id, params.map(String));
+
 const start_button = document.getElementById('start');
 const next_button = document.getElementById('next');
+const hint_button = document.getElementById('hint');
+
+start_button.textContent = message('button_start');
+next_button.textContent = message('button_next');
+hint_button.textContent = message('button_hint');
 const answer_label = document.getElementById('answer_label');
 const task_label = document.getElementById('task_label');
 const answer_input = document.getElementById('answer_input');
+const pairs_container = document.getElementById('pairs_container');
 
 // Init buttons
 const answerButtons = document.querySelectorAll('.btn-answer');
@@ -23,16 +87,13 @@ answer_input.addEventListener('change', () => {
 })
 
 const answerClickEvent = (event) => {
-    if (game.check_answer(Number(event.target.dataset.num))) {
-        event.target.classList.add('success');
-        answer_label.textContent = game.get_correct_message();
-    } else {
-        event.target.classList.add('danger');
-        answer_label.textContent = game.get_incorrent_message();
-    }
+    const verdict = game.check_answer(Number(event.target.dataset.num));
+    event.target.classList.add(verdict.correct ? 'success' : 'danger');
+    answer_label.textContent = verdict.message;
 
     next_button.style.visibility = 'visible';
     counter_state.increment_counter();
+    saveResults();
 };
 
 answerButtons.forEach((btn) => btn.addEventListener('click', answerClickEvent));
@@ -41,16 +102,18 @@ const prepareGame = () => {
     answerButtons.forEach((btn) => btn.style.visibility = 'hidden');
     next_button.style.visibility = 'hidden';
     answer_input.style.visibility = 'hidden';
+    pairs_container.style.display = 'none';
+    hint_button.style.visibility = 'hidden';
 }
 
 prepareGame();
 
-const createExerciseChoise = () => {
+const createExerciseChoise = (exercise) => {
     answer_input.style.visibility = 'hidden';
     answer_input.style.height = "0px";
-    const variants = game.get_answers();
-    answer_label.textContent = "Select one answer";
-    task_label.textContent = game.get_task();
+    const variants = exercise.options;
+    answer_label.textContent = message('select_one_answer');
+    task_label.textContent = exercise.prompt.text;
     answerButtonsContainer.style.height = defaultButtonsHeight;
     answerButtons.forEach((btn) => {
         btn.classList.remove('success', 'danger');
@@ -64,10 +127,10 @@ const createExerciseChoise = () => {
     return true;
 };
 
-const createExerciseInput = () => {
+const createExerciseInput = (exercise) => {
     answer_input.classList.remove('success', 'danger')
-    answer_label.textContent = "Type in the answer. ß=ss, ö=oe etc.";
-    task_label.textContent = game.get_task();
+    answer_label.textContent = exercise.input_hint;
+    task_label.textContent = exercise.prompt.text;
 
     answerButtons.forEach((btn) => btn.style.visibility = 'hidden');
     answer_input.style.height = defaultInputHeight;
@@ -77,49 +140,162 @@ const createExerciseInput = () => {
     answer_input.value = "";
     answer_input.focus();
     start_button.style.visibility = 'visible';
+    hint_button.style.visibility = 'visible';
+
+    return true;
+};
+
+const createExercisePairs = (exercise) => {
+    const pairs = exercise.pairs;
+    answer_label.textContent = message('select_pairs');
+    task_label.textContent = exercise.prompt.text;
+
+    answerButtons.forEach((btn) => btn.style.visibility = 'hidden');
+    answerButtonsContainer.style.height = "0px";
+    answer_input.style.visibility = 'hidden';
+    answer_input.style.height = "0px";
+    start_button.style.visibility = 'hidden';
+
+    const chosen = new Array(pairs.words.length).fill(null);
+    let selected = null;
+    const makeButton = (text) => {
+        const btn = document.createElement('button');
+        btn.classList.add('btn');
+        btn.textContent = text;
+        return btn;
+    };
+    const wordButtons = pairs.words.map(makeButton);
+    const translationButtons = pairs.translations.map(makeButton);
+
+    const checkPairs = () => {
+        const verdict = game.check_answer_pairs(Uint32Array.from(chosen));
+        verdict.pairs.forEach((correct, i) => {
+            wordButtons[i].classList.add(correct ? 'success' : 'danger');
+            translationButtons[chosen[i]].classList.add(correct ? 'success' : 'danger');
+        });
+        answer_label.textContent = verdict.message;
+        next_button.style.visibility = 'visible';
+        counter_state.increment_counter();
+        saveResults();
+    };
 
+    wordButtons.forEach((btn, i) => btn.addEventListener('click', () => {
+        wordButtons.forEach((b) => b.classList.remove('selected'));
+        btn.classList.add('selected');
+        selected = i;
+    }));
+    translationButtons.forEach((btn, j) => btn.addEventListener('click', () => {
+        if (selected === null) {
+            return;
+        }
+        chosen[selected] = j;
+        wordButtons[selected].classList.remove('selected');
+        wordButtons[selected].disabled = true;
+        btn.disabled = true;
+        selected = null;
+        if (chosen.every((c) => c !== null)) {
+            checkPairs();
+        }
+    }));
+
+    pairs_container.replaceChildren(...wordButtons.flatMap((btn, i) => [btn, translationButtons[i]]));
+    pairs_container.style.display = 'grid';
     return true;
 };
 
 // Init game
+const showSessionSummary = () => {
+    const summary = game.get_session_summary();
+    prepareGame();
+    task_label.textContent = message('session_finished');
+    answer_label.textContent = message('session_result', summary.correct, summary.exercises);
+}
+
 const createExercise = () => {
-    if (!game.create_exercise()) {
+    let exercise;
+    try {
+        exercise = game.create_exercise();
+    } catch (error) {
         console.error('Failed to create an exercise');
+        console.error(error);
+        prepareGame();
+        answer_label.textContent = error.message;
+        return false;
+    }
+    if (exercise === undefined) {
+        showSessionSummary();
         return false;
     }
     next_button.style.visibility = 'hidden';
-    if (game.is_exercise_input()) {
-        return createExerciseInput();
-    } else {
-        return createExerciseChoise();
+    pairs_container.style.display = 'none';
+    hint_button.style.visibility = 'hidden';
+    switch (exercise.kind) {
+        case 'pairs':
+            return createExercisePairs(exercise);
+        case 'text':
+        case 'verb_form':
+            return createExerciseInput(exercise);
+        default:
+            return createExerciseChoise(exercise);
     }
 
 }
 
+const onHint = () => {
+    const hint = game.request_hint();
+    if (hint === undefined) {
+        hint_button.style.visibility = 'hidden';
+        return;
+    }
+    answer_label.textContent = hint;
+    answer_input.focus();
+}
+
 const onSubmit = () => {
     start_button.style.visibility = 'hidden';
-    const res = game.check_answer_input(answer_input.value);
-    if (res) {
-        answer_label.textContent = game.get_correct_message();
-        answer_input.value = game.get_correct_spelling();
+    hint_button.style.visibility = 'hidden';
+    const verdict = game.check_answer_input(answer_input.value);
+    answer_label.textContent = verdict.message;
+    if (verdict.correct) {
+        answer_input.value = verdict.correct_answer;
         answer_input.classList.add('success');
     } else {
-        answer_label.textContent = game.get_incorrent_message();
         answer_input.classList.add('danger');
     }
 
     counter_state.increment_counter();
+    saveResults();
     next_button.style.visibility = 'visible';
     next_button.focus();
 }
 
+// Vocabulary preprocessed to JSON from the "words" parameter of the page, the workbook otherwise
+const loadWords = () => {
+    const url = new URLSearchParams(window.location.search).get('words');
+    if (!url) {
+        return game.fetch_words();
+    }
+    return fetch(url)
+        .then((response) => {
+            if (!response.ok) {
+                throw new Error(`Failed to load ${url}: ${response.status} ${response.statusText}`);
+            }
+            return response.text();
+        })
+        .then((json) => game.load_words_json(json));
+}
+
 const onStart = () => {
     try {
-        answer_label.textContent = 'Loading...';
-        game.fetch_words().then((res) => {
-            answer_label.textContent = 'Words in vocabulary: ' + res.toString();
+        answer_label.textContent = message('loading');
+        loadWords().then((res) => {
+            answer_label.textContent = message('words_in_vocabulary', res);
+            applyNativeLanguage();
             setupSubmitButton();
             createExercise();
+        }).catch((error) => {
+            console.error(error);
+            answer_label.textContent = error.message;
         });
     } catch (error) {
         console.error('Failed to fetch data');
@@ -129,7 +305,7 @@ const onStart = () => {
 }
 
 const setupSubmitButton = () => {
-    start_button.textContent = "Submit";
+    start_button.textContent = message('button_submit');
     start_button.classList.remove('success');
     start_button.classList.add('warning');
     start_button.style.visibility = 'hidden';
@@ -139,6 +315,7 @@ const setupSubmitButton = () => {
 
 // Init Controls buttons
 start_button.addEventListener('click', onStart);
+hint_button.addEventListener('click', onHint);
 next_button.addEventListener('click', () => {
     createExercise()
 });